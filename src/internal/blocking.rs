@@ -0,0 +1,143 @@
+use crate::internal::db::Store;
+use crate::internal::error::CommandError;
+use crate::internal::resp::RespValue;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::{self, Instant};
+
+/// The key an element was popped from, and the element itself.
+pub type Served = (Vec<u8>, Vec<u8>);
+
+/// What a client blocked in `BLPOP`, `BRPOP` or `BLMOVE` is waiting for.
+pub struct ListWait {
+    pub keys: Vec<Vec<u8>>,
+    pub from_head: bool,
+    /// For `BLMOVE`: the list to push the element onto, and at which end.
+    pub target: Option<(Vec<u8>, bool)>,
+}
+
+pub struct Waiter {
+    pub wait: ListWait,
+    pub sender: oneshot::Sender<Result<Served, CommandError>>,
+}
+
+/// Clients blocked on the keys of one database.
+///
+/// Each key keeps its waiters in arrival order, so the client that has
+/// been blocked the longest is served first.
+#[derive(Default)]
+pub struct BlockedClients {
+    by_key: HashMap<Vec<u8>, VecDeque<u64>>,
+    waiters: HashMap<u64, Waiter>,
+}
+
+impl BlockedClients {
+    pub fn add(
+        &mut self,
+        client: u64,
+        wait: ListWait,
+    ) -> oneshot::Receiver<Result<Served, CommandError>> {
+        let (sender, receiver) = oneshot::channel();
+        for key in &wait.keys {
+            self.by_key
+                .entry(key.clone())
+                .or_default()
+                .push_back(client);
+        }
+        self.waiters.insert(client, Waiter { wait, sender });
+        receiver
+    }
+
+    /// Forgets `client` on every key it was blocked on.
+    pub fn remove(&mut self, client: u64) -> Option<Waiter> {
+        let waiter = self.waiters.remove(&client)?;
+        for key in &waiter.wait.keys {
+            if let Some(queue) = self.by_key.get_mut(key) {
+                queue.retain(|&id| id != client);
+                if queue.is_empty() {
+                    self.by_key.remove(key);
+                }
+            }
+        }
+        Some(waiter)
+    }
+
+    pub fn is_waited_on(&self, key: &[u8]) -> bool {
+        self.by_key.contains_key(key)
+    }
+
+    /// Takes the longest-blocked client waiting on `key`.
+    pub fn pop_waiter(&mut self, key: &[u8]) -> Option<Waiter> {
+        let client = *self.by_key.get(key)?.front()?;
+        self.remove(client)
+    }
+}
+
+/// A command parked until one of its keys can serve it.
+///
+/// Dropping it, e.g. because the client disconnected, unregisters the
+/// client so no element is ever handed to a connection that is gone.
+pub struct Blocked {
+    store: Arc<Store>,
+    db: usize,
+    client: u64,
+    receiver: oneshot::Receiver<Result<Served, CommandError>>,
+    deadline: Option<Instant>,
+    /// Builds the reply from what was served, or `None` on timeout.
+    reply: fn(Option<Served>) -> RespValue,
+}
+
+impl Blocked {
+    /// `timeout` of `None` blocks until the client is served.
+    pub fn new(
+        store: Arc<Store>,
+        db: usize,
+        client: u64,
+        receiver: oneshot::Receiver<Result<Served, CommandError>>,
+        timeout: Option<Duration>,
+        reply: fn(Option<Served>) -> RespValue,
+    ) -> Self {
+        Blocked {
+            store,
+            db,
+            client,
+            receiver,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            reply,
+        }
+    }
+
+    /// Waits for the reply. Cancel safe: dropping the future and calling
+    /// `wait` again keeps the original deadline.
+    pub async fn wait(&mut self) -> RespValue {
+        let received = match self.deadline {
+            Some(deadline) => time::timeout_at(deadline, &mut self.receiver).await.ok(),
+            None => Some((&mut self.receiver).await),
+        };
+        let served = match received {
+            Some(Ok(served)) => served,
+            _ => {
+                self.store.db(self.db).unblock(self.client);
+                // The client may have been served between the timer firing
+                // and the unblock above; that element must not be lost.
+                match self.receiver.try_recv() {
+                    Ok(served) => served,
+                    Err(_) => return (self.reply)(None),
+                }
+            }
+        };
+        match served {
+            Ok(served) => (self.reply)(Some(served)),
+            Err(err) => err.into(),
+        }
+    }
+}
+
+impl Drop for Blocked {
+    fn drop(&mut self) {
+        self.store.db(self.db).unblock(self.client);
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+use std::sync::Mutex;
+
+/// Source of time for everything TTL related.
+///
+/// Deadlines are kept as monotonic `Instant`s so that stepping the wall
+/// clock never makes keys expire early or live forever; the Unix time is
+/// only consulted to translate `EXPIREAT`-style absolute timestamps.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    fn system_time(&self) -> SystemTime;
+
+    /// Wall-clock milliseconds since the Unix epoch.
+    fn unix_millis(&self) -> i64 {
+        match self.system_time().duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_millis() as i64,
+            Err(before) => -(before.duration().as_millis() as i64),
+        }
+    }
+
+    /// The monotonic deadline matching the Unix timestamp `millis`.
+    fn instant_at_unix_millis(&self, millis: i64) -> Instant {
+        let target = UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64);
+        let now = self.now();
+        match target.duration_since(self.system_time()) {
+            Ok(ahead) => now + ahead,
+            // Instants cannot go back past boot; anything that far in the
+            // past is simply "already expired".
+            Err(behind) => now.checked_sub(behind.duration()).unwrap_or(now),
+        }
+    }
+
+    /// The Unix timestamp, in milliseconds, at which `instant` falls.
+    fn unix_millis_at(&self, instant: Instant) -> i64 {
+        let now = self.now();
+        let wall = self.system_time();
+        let at = if instant >= now {
+            wall + (instant - now)
+        } else {
+            wall - (now - instant)
+        };
+        // Round rather than truncate so that a deadline set from a whole
+        // millisecond reads back as the same millisecond.
+        match at.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => ((elapsed.as_nanos() + 500_000) / 1_000_000) as i64,
+            Err(_) => 0,
+        }
+    }
+}
+
+/// The real clock used by the server.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic TTL tests.
+#[cfg(test)]
+pub struct ManualClock {
+    origin: Instant,
+    state: Mutex<ManualState>,
+}
+
+#[cfg(test)]
+struct ManualState {
+    elapsed: Duration,
+    wall: SystemTime,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock {
+            origin: Instant::now(),
+            state: Mutex::new(ManualState {
+                elapsed: Duration::ZERO,
+                // Start on a whole millisecond so Unix timestamps convert
+                // back and forth exactly.
+                wall: UNIX_EPOCH + Duration::from_millis(SystemClock.unix_millis() as u64),
+            }),
+        }
+    }
+
+    /// Moves both the monotonic and the wall clock forward.
+    pub fn advance(&self, by: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.elapsed += by;
+        state.wall += by;
+    }
+
+    /// Steps only the wall clock, like an NTP correction or a manual
+    /// `date` change would.
+    pub fn jump_wall_clock(&self, by_millis: i64) {
+        let mut state = self.state.lock().unwrap();
+        let by = Duration::from_millis(by_millis.unsigned_abs());
+        if by_millis >= 0 {
+            state.wall += by;
+        } else {
+            state.wall -= by;
+        }
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.origin + self.state.lock().unwrap().elapsed
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.state.lock().unwrap().wall
+    }
+}
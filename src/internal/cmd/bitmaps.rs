@@ -0,0 +1,826 @@
+use super::strings::MAX_STRING_LEN;
+use super::table::{CommandSpec, Flag, KeySpec};
+use super::{parse_int, CommandExecutor, CommandResult};
+use crate::internal::db::Db;
+use crate::internal::error::CommandError;
+use crate::internal::resp::RespValue;
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "setbit",
+        arity: 4,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::FIRST,
+        group: "bitmap",
+        summary: "Sets or clears the bit at offset of the string value. Creates the key if it doesn't exist.",
+        handler: setbit,
+    },
+    CommandSpec {
+        name: "getbit",
+        arity: 3,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "bitmap",
+        summary: "Returns a bit value by offset.",
+        handler: getbit,
+    },
+    CommandSpec {
+        name: "bitcount",
+        arity: -2,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "bitmap",
+        summary: "Counts the number of set bits (population counting) in a string.",
+        handler: bitcount,
+    },
+    CommandSpec {
+        name: "bitpos",
+        arity: -3,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "bitmap",
+        summary: "Finds the first set (1) or clear (0) bit in a string.",
+        handler: bitpos,
+    },
+    CommandSpec {
+        name: "bitop",
+        arity: -4,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::new(2, -1, 1),
+        group: "bitmap",
+        summary: "Performs bitwise operations on multiple strings, and stores the result.",
+        handler: bitop,
+    },
+    CommandSpec {
+        name: "bitfield",
+        arity: -2,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::FIRST,
+        group: "bitmap",
+        summary: "Performs arbitrary bitfield integer operations on strings.",
+        handler: bitfield,
+    },
+    CommandSpec {
+        name: "bitfield_ro",
+        arity: -2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "bitmap",
+        summary: "Performs arbitrary read-only bitfield integer operations on strings.",
+        handler: bitfield_ro,
+    },
+];
+
+fn custom(message: &str) -> CommandError {
+    CommandError::Custom(String::from(message))
+}
+
+/// Checks a bit offset; like Redis, bitmaps may not outgrow a bulk string.
+fn bit_offset(offset: i64) -> Result<usize, CommandError> {
+    if offset < 0 || (offset as u64 >> 3) >= MAX_STRING_LEN as u64 {
+        return Err(custom("ERR bit offset is not an integer or out of range"));
+    }
+    Ok(offset as usize)
+}
+
+fn parse_bit_offset(arg: &[u8]) -> Result<usize, CommandError> {
+    bit_offset(parse_int::<i64>(arg).unwrap_or(-1))
+}
+
+/// The string at `key`, created empty if missing and zero-padded to at
+/// least `min_len` bytes.
+fn bitmap_mut<'a>(
+    db: &'a mut Db,
+    key: &[u8],
+    min_len: usize,
+) -> Result<&'a mut Vec<u8>, CommandError> {
+    if db.string_mut(key)?.is_none() {
+        db.set(key.to_vec(), vec![], None);
+    }
+    let value = db.string_mut(key)?.expect("bitmap was just created");
+    if value.len() < min_len {
+        value.resize(min_len, 0);
+    }
+    Ok(value)
+}
+
+/// Bit `offset` of `bytes`, counting from the most significant bit of the
+/// first byte; bits past the end read as zero.
+fn bit_at(bytes: &[u8], offset: usize) -> u8 {
+    bytes
+        .get(offset >> 3)
+        .map_or(0, |byte| (byte >> (7 - (offset & 7))) & 1)
+}
+
+fn set_bit_at(bytes: &mut [u8], offset: usize, on: bool) {
+    let mask = 1 << (7 - (offset & 7));
+    if on {
+        bytes[offset >> 3] |= mask;
+    } else {
+        bytes[offset >> 3] &= !mask;
+    }
+}
+
+fn setbit(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let offset = parse_bit_offset(args[2])?;
+    let on = match args[3] {
+        b"0" => false,
+        b"1" => true,
+        _ => return Err(custom("ERR bit is not an integer or out of range")),
+    };
+    let mut db = executor.db();
+    let bitmap = bitmap_mut(&mut db, args[1], (offset >> 3) + 1)?;
+    let old = bit_at(bitmap, offset);
+    set_bit_at(bitmap, offset, on);
+    Ok(RespValue::Integer(old as i64))
+}
+
+fn getbit(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let offset = parse_bit_offset(args[2])?;
+    let bit = executor
+        .db()
+        .string_mut(args[1])?
+        .map_or(0, |bitmap| bit_at(bitmap, offset));
+    Ok(RespValue::Integer(bit as i64))
+}
+
+/// A `start end [BYTE | BIT]` range, resolved against a string of `len`
+/// bytes into an inclusive range of bit offsets; `None` when it is empty.
+fn bit_range(len: usize, start: i64, end: i64, bit_unit: bool) -> Option<(usize, usize)> {
+    let total = if bit_unit { len * 8 } else { len } as i64;
+    let resolve = |index: i64| {
+        if index < 0 {
+            (total + index).max(0)
+        } else {
+            index
+        }
+    };
+    let start = resolve(start);
+    let end = resolve(end).min(total - 1);
+    if total == 0 || start > end {
+        return None;
+    }
+    let (start, end) = (start as usize, end as usize);
+    Some(if bit_unit {
+        (start, end)
+    } else {
+        (start * 8, end * 8 + 7)
+    })
+}
+
+/// Masks selecting the bits of the first and last byte of the inclusive
+/// bit range `first..=last`.
+fn edge_masks(first: usize, last: usize) -> (u8, u8) {
+    (0xff >> (first % 8), 0xff << (7 - last % 8))
+}
+
+/// Set bits among the offsets `first..=last`; whole bytes are counted at
+/// once and only the edge bytes are masked.
+fn count_bits(bitmap: &[u8], first: usize, last: usize) -> i64 {
+    let (first_byte, last_byte) = (first / 8, last / 8);
+    let (head, tail) = edge_masks(first, last);
+    if first_byte == last_byte {
+        return (bitmap[first_byte] & head & tail).count_ones() as i64;
+    }
+    let middle: u64 = bitmap[first_byte + 1..last_byte]
+        .iter()
+        .map(|byte| byte.count_ones() as u64)
+        .sum();
+    ((bitmap[first_byte] & head).count_ones() as u64
+        + middle
+        + (bitmap[last_byte] & tail).count_ones() as u64) as i64
+}
+
+/// The first offset in `first..=last` holding `bit`, skipping whole bytes
+/// that cannot contain it.
+fn find_bit(bitmap: &[u8], first: usize, last: usize, bit: u8) -> Option<usize> {
+    let (first_byte, last_byte) = (first / 8, last / 8);
+    let (head, tail) = edge_masks(first, last);
+    (first_byte..=last_byte).find_map(|index| {
+        // Searching for a clear bit is searching the inverted byte for a set one.
+        let mut byte = if bit == 1 {
+            bitmap[index]
+        } else {
+            !bitmap[index]
+        };
+        if index == first_byte {
+            byte &= head;
+        }
+        if index == last_byte {
+            byte &= tail;
+        }
+        (byte != 0).then(|| index * 8 + byte.leading_zeros() as usize)
+    })
+}
+
+fn parse_range_unit(arg: Option<&&[u8]>) -> Result<bool, CommandError> {
+    match arg.map(|unit| unit.to_ascii_uppercase()) {
+        None => Ok(false),
+        Some(unit) if unit == b"BYTE" => Ok(false),
+        Some(unit) if unit == b"BIT" => Ok(true),
+        Some(_) => Err(CommandError::Syntax),
+    }
+}
+
+fn bitcount(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let range = match args.len() {
+        2 => None,
+        4 | 5 => Some((
+            parse_int::<i64>(args[2])?,
+            parse_int::<i64>(args[3])?,
+            parse_range_unit(args.get(4))?,
+        )),
+        _ => return Err(CommandError::Syntax),
+    };
+    let mut db = executor.db();
+    let Some(bitmap) = db.string_mut(args[1])? else {
+        return Ok(RespValue::Integer(0));
+    };
+    let count = match range {
+        None => bitmap.iter().map(|byte| byte.count_ones() as i64).sum(),
+        Some((start, end, bit_unit)) => match bit_range(bitmap.len(), start, end, bit_unit) {
+            Some((first, last)) => count_bits(bitmap, first, last),
+            None => 0,
+        },
+    };
+    Ok(RespValue::Integer(count))
+}
+
+fn bitpos(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let bit = match args[2] {
+        b"0" => 0,
+        b"1" => 1,
+        _ => return Err(custom("ERR The bit argument must be 1 or 0.")),
+    };
+    if args.len() > 6 {
+        return Err(CommandError::Syntax);
+    }
+    let start = args.get(3).map(|arg| parse_int::<i64>(arg)).transpose()?;
+    let end = args.get(4).map(|arg| parse_int::<i64>(arg)).transpose()?;
+    let bit_unit = parse_range_unit(args.get(5))?;
+
+    let mut db = executor.db();
+    let Some(bitmap) = db.string_mut(args[1])? else {
+        return Ok(RespValue::Integer(if bit == 1 { -1 } else { 0 }));
+    };
+    let Some((first, last)) = bit_range(
+        bitmap.len(),
+        start.unwrap_or(0),
+        end.unwrap_or(-1),
+        bit_unit,
+    ) else {
+        return Ok(RespValue::Integer(-1));
+    };
+    if let Some(pos) = find_bit(bitmap, first, last, bit) {
+        return Ok(RespValue::Integer(pos as i64));
+    }
+    // Without an explicit end the string counts as padded with zeros, so
+    // the first clear bit is the one just past it.
+    Ok(RespValue::Integer(if bit == 0 && end.is_none() {
+        bitmap.len() as i64 * 8
+    } else {
+        -1
+    }))
+}
+
+fn bitop(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let op = args[1].to_ascii_uppercase();
+    let sources = &args[3..];
+    match op.as_slice() {
+        b"AND" | b"OR" | b"XOR" => {}
+        b"NOT" if sources.len() != 1 => {
+            return Err(custom(
+                "ERR BITOP NOT must be called with a single source key.",
+            ))
+        }
+        b"NOT" => {}
+        b"DIFF" if sources.len() < 2 => {
+            return Err(custom(
+                "ERR BITOP DIFF must be called with at least two source keys.",
+            ))
+        }
+        b"DIFF" => {}
+        _ => return Err(CommandError::Syntax),
+    }
+
+    let mut db = executor.db();
+    let mut values = Vec::with_capacity(sources.len());
+    for key in sources {
+        values.push(db.get(key)?.unwrap_or_default());
+    }
+    let len = values.iter().map(Vec::len).max().unwrap_or(0);
+    let byte = |value: &Vec<u8>, i: usize| value.get(i).copied().unwrap_or(0);
+    let result: Vec<u8> = (0..len)
+        .map(|i| {
+            let rest = values[1..].iter().map(|value| byte(value, i));
+            let first = byte(&values[0], i);
+            match op.as_slice() {
+                b"AND" => rest.fold(first, |acc, b| acc & b),
+                b"OR" => rest.fold(first, |acc, b| acc | b),
+                b"XOR" => rest.fold(first, |acc, b| acc ^ b),
+                b"NOT" => !first,
+                _ => first & !rest.fold(0, |acc, b| acc | b),
+            }
+        })
+        .collect();
+
+    if result.is_empty() {
+        db.remove(args[2]);
+    } else {
+        db.set(args[2].to_vec(), result, None);
+    }
+    Ok(RespValue::Integer(len as i64))
+}
+
+/// An `i<bits>` or `u<bits>` bitfield type.
+#[derive(Clone, Copy)]
+struct FieldType {
+    signed: bool,
+    bits: u32,
+}
+
+impl FieldType {
+    fn parse(arg: &[u8]) -> Result<Self, CommandError> {
+        let error = || {
+            custom(
+                "ERR Invalid bitfield type. Use something like i16 u8. \
+                 Note that u64 is not supported but i64 is.",
+            )
+        };
+        let (signed, bits) = match arg.split_first() {
+            Some((b'i' | b'I', bits)) => (true, bits),
+            Some((b'u' | b'U', bits)) => (false, bits),
+            _ => return Err(error()),
+        };
+        let bits = parse_int::<u32>(bits).map_err(|_| error())?;
+        let max_bits = if signed { 64 } else { 63 };
+        if bits == 0 || bits > max_bits {
+            return Err(error());
+        }
+        Ok(FieldType { signed, bits })
+    }
+
+    fn min(self) -> i128 {
+        if self.signed {
+            -(1 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    fn max(self) -> i128 {
+        if self.signed {
+            (1 << (self.bits - 1)) - 1
+        } else {
+            (1 << self.bits) - 1
+        }
+    }
+
+    /// Reduces `value` modulo `2^bits` into the range of this type.
+    fn wrap(self, value: i128) -> i128 {
+        let modulus = 1i128 << self.bits;
+        let wrapped = value.rem_euclid(modulus);
+        if self.signed && wrapped > self.max() {
+            wrapped - modulus
+        } else {
+            wrapped
+        }
+    }
+
+    fn read(self, bytes: &[u8], offset: usize) -> i128 {
+        let raw = (0..self.bits as usize)
+            .fold(0u64, |acc, i| (acc << 1) | bit_at(bytes, offset + i) as u64);
+        self.wrap(raw as i128)
+    }
+
+    fn write(self, bytes: &mut [u8], offset: usize, value: i128) {
+        let raw = value.rem_euclid(1i128 << self.bits) as u64;
+        for i in 0..self.bits as usize {
+            set_bit_at(
+                bytes,
+                offset + i,
+                (raw >> (self.bits as usize - 1 - i)) & 1 == 1,
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Overflow {
+    Wrap,
+    Sat,
+    Fail,
+}
+
+impl Overflow {
+    /// Fits `value` into `field`, or `None` when it overflows under `FAIL`.
+    fn apply(self, field: FieldType, value: i128) -> Option<i128> {
+        if (field.min()..=field.max()).contains(&value) {
+            return Some(value);
+        }
+        match self {
+            Overflow::Wrap => Some(field.wrap(value)),
+            Overflow::Sat => Some(value.clamp(field.min(), field.max())),
+            Overflow::Fail => None,
+        }
+    }
+}
+
+enum FieldOp {
+    Get,
+    Set(i64),
+    IncrBy(i64),
+}
+
+struct BitfieldOp {
+    op: FieldOp,
+    field: FieldType,
+    offset: usize,
+    overflow: Overflow,
+}
+
+fn parse_bitfield_ops(args: &[&[u8]]) -> Result<Vec<BitfieldOp>, CommandError> {
+    let mut ops = vec![];
+    let mut overflow = Overflow::Wrap;
+    let mut idx = 0;
+    while idx < args.len() {
+        let subcommand = args[idx].to_ascii_uppercase();
+        if subcommand == b"OVERFLOW" {
+            let kind = args.get(idx + 1).ok_or(CommandError::Syntax)?;
+            overflow = match kind.to_ascii_uppercase().as_slice() {
+                b"WRAP" => Overflow::Wrap,
+                b"SAT" => Overflow::Sat,
+                b"FAIL" => Overflow::Fail,
+                _ => return Err(custom("ERR Invalid OVERFLOW type specified")),
+            };
+            idx += 2;
+            continue;
+        }
+        let operands = match subcommand.as_slice() {
+            b"GET" => 2,
+            b"SET" | b"INCRBY" => 3,
+            _ => return Err(CommandError::Syntax),
+        };
+        if idx + operands >= args.len() {
+            return Err(CommandError::Syntax);
+        }
+        let field = FieldType::parse(args[idx + 1])?;
+        // `#N` addresses the N-th field of this width.
+        let offset = match args[idx + 2].strip_prefix(b"#") {
+            Some(index) => bit_offset(
+                parse_int::<i64>(index)
+                    .unwrap_or(-1)
+                    .saturating_mul(field.bits as i64),
+            )?,
+            None => parse_bit_offset(args[idx + 2])?,
+        };
+        let op = match subcommand.as_slice() {
+            b"GET" => FieldOp::Get,
+            b"SET" => FieldOp::Set(parse_int(args[idx + 3])?),
+            _ => FieldOp::IncrBy(parse_int(args[idx + 3])?),
+        };
+        ops.push(BitfieldOp {
+            op,
+            field,
+            offset,
+            overflow,
+        });
+        idx += operands + 1;
+    }
+    Ok(ops)
+}
+
+fn bitfield_generic(
+    executor: &mut CommandExecutor,
+    args: &[&[u8]],
+    read_only: bool,
+) -> CommandResult {
+    let ops = parse_bitfield_ops(&args[2..])?;
+    let writes = ops.iter().any(|op| !matches!(op.op, FieldOp::Get));
+    if read_only && writes {
+        return Err(custom("ERR BITFIELD_RO only supports the GET subcommand"));
+    }
+
+    let mut db = executor.db();
+    if !writes {
+        let bitmap = db.string_mut(args[1])?;
+        let bytes = bitmap.map_or(&[][..], |bitmap| bitmap.as_slice());
+        return Ok(RespValue::Array(
+            ops.iter()
+                .map(|op| RespValue::Integer(op.field.read(bytes, op.offset) as i64))
+                .collect(),
+        ));
+    }
+
+    let min_len = ops
+        .iter()
+        .map(|op| (op.offset + op.field.bits as usize).div_ceil(8))
+        .max()
+        .unwrap_or(0);
+    let bitmap = bitmap_mut(&mut db, args[1], min_len)?;
+    let mut replies = Vec::with_capacity(ops.len());
+    for op in ops {
+        let old = op.field.read(bitmap, op.offset);
+        let (new, reply) = match op.op {
+            FieldOp::Get => (None, Some(old)),
+            FieldOp::Set(value) => {
+                // Unsigned fields see the argument as its 64-bit pattern,
+                // so a negative value wraps or saturates at the top.
+                let value = if op.field.signed {
+                    value as i128
+                } else {
+                    value as u64 as i128
+                };
+                let new = op.overflow.apply(op.field, value);
+                (new, new.map(|_| old))
+            }
+            FieldOp::IncrBy(delta) => {
+                let new = op.overflow.apply(op.field, old + delta as i128);
+                (new, new)
+            }
+        };
+        if let Some(new) = new {
+            op.field.write(bitmap, op.offset, new);
+        }
+        replies.push(match reply {
+            Some(value) => RespValue::Integer(value as i64),
+            None => RespValue::Null,
+        });
+    }
+    Ok(RespValue::Array(replies))
+}
+
+fn bitfield(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    bitfield_generic(executor, args, false)
+}
+
+fn bitfield_ro(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    bitfield_generic(executor, args, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internal::clock::SystemClock;
+    use crate::internal::cmd::{tests::command, CommandExecutor};
+    use crate::internal::db::{Store, DEFAULT_DATABASES};
+    use crate::internal::resp::RespValue;
+
+    use std::sync::Arc;
+
+    fn raw_command(parts: &[&[u8]]) -> RespValue {
+        RespValue::Array(
+            parts
+                .iter()
+                .map(|p| RespValue::BulkString(p.to_vec()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn bit_access_and_counting() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let int = RespValue::Integer;
+
+        assert_eq!(
+            command(&["SETBIT", "b", "7", "1"]).accept(&mut client),
+            int(0)
+        );
+        assert_eq!(
+            command(&["SETBIT", "b", "7", "1"]).accept(&mut client),
+            int(1)
+        );
+        assert_eq!(command(&["GETBIT", "b", "7"]).accept(&mut client), int(1));
+        assert_eq!(command(&["GETBIT", "b", "0"]).accept(&mut client), int(0));
+        assert_eq!(command(&["GETBIT", "b", "100"]).accept(&mut client), int(0));
+        assert_eq!(
+            command(&["GET", "b"]).accept(&mut client),
+            RespValue::BulkString(vec![1])
+        );
+        assert_eq!(
+            command(&["SETBIT", "b", "-1", "1"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR bit offset is not an integer or out of range"
+            ))
+        );
+        assert_eq!(
+            command(&["SETBIT", "b", "1", "2"]).accept(&mut client),
+            RespValue::Error(String::from("ERR bit is not an integer or out of range"))
+        );
+
+        command(&["SET", "s", "foobar"]).accept(&mut client);
+        for (range, expected) in [
+            (&[][..], 26),
+            (&["0", "0"], 4),
+            (&["1", "1"], 6),
+            (&["1", "1", "BYTE"], 6),
+            (&["5", "30", "BIT"], 17),
+            (&["2", "4", "BIT"], 1),
+            (&["9", "9", "BIT"], 1),
+            (&["7", "8", "BIT"], 0),
+            (&["-2", "-1"], 7),
+            (&["3", "1"], 0),
+        ] {
+            let mut parts = vec!["BITCOUNT", "s"];
+            parts.extend(range);
+            assert_eq!(
+                command(&parts).accept(&mut client),
+                int(expected),
+                "{:?}",
+                range
+            );
+        }
+        assert_eq!(
+            command(&["BITCOUNT", "missing"]).accept(&mut client),
+            int(0)
+        );
+        assert_eq!(
+            command(&["BITCOUNT", "s", "0"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+
+        raw_command(&[b"SET", b"p", b"\xff\xf0\x00"]).accept(&mut client);
+        assert_eq!(command(&["BITPOS", "p", "0"]).accept(&mut client), int(12));
+        raw_command(&[b"SET", b"p", b"\x00\xff\xf0"]).accept(&mut client);
+        for (range, expected) in [
+            (&["1", "0"][..], 8),
+            (&["1", "2"], 16),
+            (&["1", "2", "-1", "BYTE"], 16),
+            (&["1", "7", "15", "BIT"], 8),
+            (&["1", "7", "-3", "BIT"], 8),
+            (&["0", "1", "1"], -1),
+            (&["0", "9", "20", "BIT"], 20),
+            (&["0", "9", "14", "BIT"], -1),
+            (&["1", "3", "5", "BIT"], -1),
+        ] {
+            let mut parts = vec!["BITPOS", "p"];
+            parts.extend(range);
+            assert_eq!(
+                command(&parts).accept(&mut client),
+                int(expected),
+                "{:?}",
+                range
+            );
+        }
+        raw_command(&[b"SET", b"p", b"\xff\xff"]).accept(&mut client);
+        assert_eq!(command(&["BITPOS", "p", "0"]).accept(&mut client), int(16));
+        assert_eq!(
+            command(&["BITPOS", "p", "0", "0", "-1"]).accept(&mut client),
+            int(-1)
+        );
+        assert_eq!(
+            command(&["BITPOS", "missing", "0"]).accept(&mut client),
+            int(0)
+        );
+        assert_eq!(
+            command(&["BITPOS", "missing", "1"]).accept(&mut client),
+            int(-1)
+        );
+    }
+
+    #[test]
+    fn bitop_operations() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        command(&["SET", "key1", "foobar"]).accept(&mut client);
+        command(&["SET", "key2", "abcdef"]).accept(&mut client);
+        raw_command(&[b"SET", b"x", b"\xf0\x0f"]).accept(&mut client);
+        raw_command(&[b"SET", b"y", b"\x30"]).accept(&mut client);
+        raw_command(&[b"SET", b"z", b"\x80\x01\xaa"]).accept(&mut client);
+
+        for (op, keys, expected) in [
+            ("AND", &["key1", "key2"][..], &b"`bc`ab"[..]),
+            ("OR", &["key1", "key2"], b"goofev"),
+            ("XOR", &["x", "y"], b"\xc0\x0f"),
+            ("NOT", &["y"], b"\xcf"),
+            ("DIFF", &["x", "y", "z"], b"\x40\x0e\x00"),
+            ("AND", &["x", "missing"], b"\x00\x00"),
+        ] {
+            let mut parts = vec!["BITOP", op, "dest"];
+            parts.extend(keys);
+            assert_eq!(
+                command(&parts).accept(&mut client),
+                RespValue::Integer(expected.len() as i64)
+            );
+            assert_eq!(
+                command(&["GET", "dest"]).accept(&mut client),
+                RespValue::BulkString(expected.to_vec()),
+                "BITOP {}",
+                op
+            );
+        }
+
+        assert_eq!(
+            command(&["BITOP", "OR", "dest", "missing"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["GET", "dest"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["BITOP", "NOT", "dest", "x", "y"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR BITOP NOT must be called with a single source key."
+            ))
+        );
+        assert_eq!(
+            command(&["BITOP", "DIFF", "dest", "x"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR BITOP DIFF must be called with at least two source keys."
+            ))
+        );
+        assert_eq!(
+            command(&["BITOP", "NAND", "dest", "x"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+    }
+
+    #[test]
+    fn bitfield_types_and_overflow() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let ints = |values: &[i64]| {
+            RespValue::Array(values.iter().map(|v| RespValue::Integer(*v)).collect())
+        };
+
+        assert_eq!(
+            command(&["BITFIELD", "bf", "INCRBY", "i5", "100", "1", "GET", "u4", "0"])
+                .accept(&mut client),
+            ints(&[1, 0])
+        );
+        for (wrapped, saturated) in [(1, 1), (2, 2), (3, 3), (0, 3)] {
+            assert_eq!(
+                command(&[
+                    "BITFIELD", "c", "INCRBY", "u2", "100", "1", "OVERFLOW", "SAT", "INCRBY", "u2",
+                    "102", "1"
+                ])
+                .accept(&mut client),
+                ints(&[wrapped, saturated])
+            );
+        }
+        assert_eq!(
+            command(&["BITFIELD", "c", "OVERFLOW", "FAIL", "INCRBY", "u2", "102", "1"])
+                .accept(&mut client),
+            RespValue::Array(vec![RespValue::Null])
+        );
+
+        assert_eq!(
+            command(&[
+                "BITFIELD", "n", "SET", "i8", "#1", "-100", "GET", "i8", "8", "GET", "u8", "8"
+            ])
+            .accept(&mut client),
+            ints(&[0, -100, 156])
+        );
+        assert_eq!(
+            command(&["BITFIELD", "n", "SET", "u8", "0", "-1", "GET", "u8", "0"])
+                .accept(&mut client),
+            ints(&[0, 255])
+        );
+        assert_eq!(
+            command(&[
+                "BITFIELD", "n", "OVERFLOW", "SAT", "INCRBY", "i8", "8", "-100", "OVERFLOW",
+                "WRAP", "INCRBY", "i8", "8", "-1"
+            ])
+            .accept(&mut client),
+            ints(&[-128, 127])
+        );
+        assert_eq!(
+            command(&[
+                "BITFIELD", "w", "SET", "i64", "0", "-1", "GET", "i64", "0", "GET", "u63", "1"
+            ])
+            .accept(&mut client),
+            ints(&[0, -1, i64::MAX])
+        );
+
+        assert_eq!(
+            command(&["BITFIELD_RO", "missing", "GET", "u8", "0"]).accept(&mut client),
+            ints(&[0])
+        );
+        assert_eq!(
+            command(&["GET", "missing"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["BITFIELD_RO", "n", "SET", "u8", "0", "1"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR BITFIELD_RO only supports the GET subcommand"
+            ))
+        );
+        assert_eq!(
+            command(&["BITFIELD", "n", "GET", "u64", "0"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR Invalid bitfield type. Use something like i16 u8. \
+                 Note that u64 is not supported but i64 is."
+            ))
+        );
+        assert_eq!(
+            command(&["BITFIELD", "n", "OVERFLOW", "MAYBE"]).accept(&mut client),
+            RespValue::Error(String::from("ERR Invalid OVERFLOW type specified"))
+        );
+        assert_eq!(
+            command(&["BITFIELD", "n", "SET", "u8", "0"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use super::table::{CommandSpec, Flag, KeySpec};
+use super::{ok, parse_int, CommandExecutor, CommandResult};
+use crate::internal::error::CommandError;
+use crate::internal::resp::RespValue;
+
+/// Version advertised to clients; libraries gate features on it.
+pub const REDIS_VERSION: &str = "7.2.0";
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "ping",
+        arity: -1,
+        flags: &[Flag::Fast, Flag::Loading, Flag::Stale],
+        keys: KeySpec::NONE,
+        group: "connection",
+        summary: "Returns the server's liveliness response.",
+        handler: ping,
+    },
+    CommandSpec {
+        name: "echo",
+        arity: 2,
+        flags: &[Flag::Fast, Flag::Loading, Flag::Stale],
+        keys: KeySpec::NONE,
+        group: "connection",
+        summary: "Returns the given string.",
+        handler: echo,
+    },
+    CommandSpec {
+        name: "select",
+        arity: 2,
+        flags: &[Flag::Fast, Flag::Loading, Flag::Stale],
+        keys: KeySpec::NONE,
+        group: "connection",
+        summary: "Changes the selected database.",
+        handler: select,
+    },
+    CommandSpec {
+        name: "hello",
+        arity: -1,
+        flags: &[Flag::Fast, Flag::Loading, Flag::Stale],
+        keys: KeySpec::NONE,
+        group: "connection",
+        summary: "Handshakes with the Redis server.",
+        handler: hello,
+    },
+    CommandSpec {
+        name: "client",
+        arity: -2,
+        flags: &[Flag::Loading, Flag::Stale],
+        keys: KeySpec::NONE,
+        group: "connection",
+        summary: "A container for client connection commands.",
+        handler: client,
+    },
+];
+
+fn validate_client_name(name: &str) -> Result<(), CommandError> {
+    if name.bytes().any(|b| !(b'!'..=b'~').contains(&b)) {
+        return Err(CommandError::Custom(String::from(
+            "ERR Client names cannot contain spaces, newlines or special characters.",
+        )));
+    }
+    Ok(())
+}
+
+impl CommandExecutor {
+    fn hello_reply(&self) -> RespValue {
+        let field = |name: &str| RespValue::BulkString(name.as_bytes().to_vec());
+        RespValue::Map(vec![
+            (field("server"), field("redis")),
+            (field("version"), field(REDIS_VERSION)),
+            (
+                field("proto"),
+                RespValue::Integer(self.session.protocol as i64),
+            ),
+            (field("id"), RespValue::Integer(self.session.id as i64)),
+            (field("mode"), field("standalone")),
+            (field("role"), field("master")),
+            (field("modules"), RespValue::Array(vec![])),
+        ])
+    }
+
+    fn client_info(&self) -> String {
+        format!(
+            "id={} name={} db={} resp={}",
+            self.session.id,
+            self.session.name.as_deref().unwrap_or(""),
+            self.session.db,
+            self.session.protocol
+        )
+    }
+}
+
+fn ping(_executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    match args.len() {
+        1 => Ok(RespValue::SimpleString(String::from("PONG"))),
+        2 => Ok(RespValue::BulkString(args[1].to_vec())),
+        _ => Err(CommandError::WrongArity(String::from("ping"))),
+    }
+}
+
+fn echo(_executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    Ok(RespValue::BulkString(args[1].to_vec()))
+}
+
+fn select(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let index = parse_int::<i64>(args[1])?;
+    if index < 0 || index as usize >= executor.store.num_databases() {
+        return Err(CommandError::Custom(String::from(
+            "ERR DB index is out of range",
+        )));
+    }
+    executor.session.db = index as usize;
+    Ok(ok())
+}
+
+fn hello(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut protocol = executor.session.protocol;
+    let mut name = None;
+    if let Some(version) = args.get(1) {
+        protocol = match parse_int::<i64>(version) {
+            Ok(v @ 2..=3) => v as u8,
+            Ok(_) => {
+                return Err(CommandError::Custom(String::from(
+                    "NOPROTO unsupported protocol version",
+                )))
+            }
+            Err(_) => {
+                return Err(CommandError::Custom(String::from(
+                    "ERR Protocol version is not an integer or out of range",
+                )))
+            }
+        };
+        let mut idx = 2;
+        while idx < args.len() {
+            let option = String::from_utf8_lossy(args[idx]).into_owned();
+            let syntax_error =
+                || CommandError::Custom(format!("ERR Syntax error in HELLO option '{}'", option));
+            match option.to_uppercase().as_str() {
+                "AUTH" if idx + 2 < args.len() => idx += 3,
+                "SETNAME" if idx + 1 < args.len() => {
+                    name = Some(String::from_utf8_lossy(args[idx + 1]).into_owned());
+                    idx += 2;
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+    }
+    if let Some(name) = name {
+        validate_client_name(&name)?;
+        executor.session.name = if name.is_empty() { None } else { Some(name) };
+    }
+    executor.session.protocol = protocol;
+    Ok(executor.hello_reply())
+}
+
+fn client(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let subcommand = String::from_utf8_lossy(args[1]).to_uppercase();
+    match (subcommand.as_str(), args.len()) {
+        ("ID", 2) => Ok(RespValue::Integer(executor.session.id as i64)),
+        ("GETNAME", 2) => match executor.session.name {
+            Some(ref name) => Ok(RespValue::BulkString(name.clone().into_bytes())),
+            None => Ok(RespValue::Null),
+        },
+        ("SETNAME", 3) => {
+            let name = String::from_utf8_lossy(args[2]).into_owned();
+            validate_client_name(&name)?;
+            executor.session.name = if name.is_empty() { None } else { Some(name) };
+            Ok(ok())
+        }
+        ("INFO", 2) => Ok(RespValue::Verbatim {
+            format: String::from("txt"),
+            data: format!("{}\n", executor.client_info()).into_bytes(),
+        }),
+        ("ID" | "GETNAME" | "SETNAME" | "INFO", _) => Err(CommandError::WrongArity(format!(
+            "client|{}",
+            subcommand.to_lowercase()
+        ))),
+        _ => Err(CommandError::Custom(format!(
+            "ERR unknown subcommand '{}'. Try CLIENT HELP.",
+            String::from_utf8_lossy(args[1])
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internal::clock::SystemClock;
+    use crate::internal::cmd::{tests::command, CommandExecutor};
+    use crate::internal::db::{Store, DEFAULT_DATABASES};
+    use crate::internal::resp::RespValue;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn selected_db_is_per_client() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut first = CommandExecutor::new(Arc::clone(&store), Arc::new(SystemClock));
+        let mut second = CommandExecutor::new(Arc::clone(&store), Arc::new(SystemClock));
+
+        command(&["SELECT", "1"]).accept(&mut first);
+        command(&["SET", "foo", "bar"]).accept(&mut first);
+        assert_eq!(
+            command(&["GET", "foo"]).accept(&mut second),
+            RespValue::Null
+        );
+        command(&["SELECT", "1"]).accept(&mut second);
+        assert_eq!(
+            command(&["GET", "foo"]).accept(&mut second),
+            RespValue::BulkString(b"bar".to_vec())
+        );
+    }
+
+    #[test]
+    fn hello_switches_protocol() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        assert_eq!(client.protocol(), 2);
+
+        let reply = command(&["HELLO", "3", "SETNAME", "worker"]).accept(&mut client);
+        assert!(matches!(reply, RespValue::Map(_)));
+        assert_eq!(client.protocol(), 3);
+        assert_eq!(
+            command(&["CLIENT", "GETNAME"]).accept(&mut client),
+            RespValue::BulkString(b"worker".to_vec())
+        );
+
+        assert_eq!(
+            command(&["HELLO", "4"]).accept(&mut client),
+            RespValue::Error(String::from("NOPROTO unsupported protocol version"))
+        );
+        assert_eq!(client.protocol(), 3);
+    }
+}
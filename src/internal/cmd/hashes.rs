@@ -0,0 +1,681 @@
+use super::keys::{parse_cursor, scan_reply, ScanOptions};
+use super::strings::{decimal_places, format_float, stored_float, stored_int};
+use super::table::{CommandSpec, Flag, KeySpec};
+use super::{bulk_or_null, parse_int, CommandExecutor, CommandResult};
+use crate::internal::error::CommandError;
+use crate::internal::random;
+use crate::internal::resp::RespValue;
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "hset",
+        arity: -4,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Creates or modifies the value of a field in a hash.",
+        handler: hset,
+    },
+    CommandSpec {
+        name: "hsetnx",
+        arity: 4,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Sets the value of a field in a hash only when the field doesn't exist.",
+        handler: hsetnx,
+    },
+    CommandSpec {
+        name: "hget",
+        arity: 3,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Returns the value of a field in a hash.",
+        handler: hget,
+    },
+    CommandSpec {
+        name: "hmget",
+        arity: -3,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Returns the values of all fields in a hash.",
+        handler: hmget,
+    },
+    CommandSpec {
+        name: "hdel",
+        arity: -3,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain.",
+        handler: hdel,
+    },
+    CommandSpec {
+        name: "hexists",
+        arity: 3,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Determines whether a field exists in a hash.",
+        handler: hexists,
+    },
+    CommandSpec {
+        name: "hlen",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Returns the number of fields in a hash.",
+        handler: hlen,
+    },
+    CommandSpec {
+        name: "hstrlen",
+        arity: 3,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Returns the length of the value of a field.",
+        handler: hstrlen,
+    },
+    CommandSpec {
+        name: "hkeys",
+        arity: 2,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Returns all fields in a hash.",
+        handler: hkeys,
+    },
+    CommandSpec {
+        name: "hvals",
+        arity: 2,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Returns all values in a hash.",
+        handler: hvals,
+    },
+    CommandSpec {
+        name: "hgetall",
+        arity: 2,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Returns all fields and values in a hash.",
+        handler: hgetall,
+    },
+    CommandSpec {
+        name: "hincrby",
+        arity: 4,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Increments the integer value of a field in a hash by a number. Uses 0 as initial value if the field doesn't exist.",
+        handler: hincrby,
+    },
+    CommandSpec {
+        name: "hincrbyfloat",
+        arity: 4,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Increments the floating point value of a field by a number. Uses 0 as initial value if the field doesn't exist.",
+        handler: hincrbyfloat,
+    },
+    CommandSpec {
+        name: "hrandfield",
+        arity: -2,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Returns one or more random fields from a hash.",
+        handler: hrandfield,
+    },
+    CommandSpec {
+        name: "hscan",
+        arity: -3,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "hash",
+        summary: "Iterates over fields and values of a hash.",
+        handler: hscan,
+    },
+];
+
+fn hset(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    if !args.len().is_multiple_of(2) {
+        return Err(CommandError::WrongArity(String::from("hset")));
+    }
+    let mut db = executor.db();
+    let hash = db.hash_or_insert(args[1])?;
+    let added = args[2..]
+        .chunks(2)
+        .filter(|pair| hash.insert(pair[0].to_vec(), pair[1].to_vec()).is_none())
+        .count();
+    Ok(RespValue::Integer(added as i64))
+}
+
+fn hsetnx(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let hash = db.hash_or_insert(args[1])?;
+    if hash.contains_key(args[2]) {
+        return Ok(RespValue::Integer(0));
+    }
+    hash.insert(args[2].to_vec(), args[3].to_vec());
+    Ok(RespValue::Integer(1))
+}
+
+fn hget(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let value = db
+        .hash_mut(args[1])?
+        .and_then(|hash| hash.get(args[2]).cloned());
+    Ok(bulk_or_null(value))
+}
+
+fn hmget(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let hash = db.hash_mut(args[1])?;
+    Ok(RespValue::Array(
+        args[2..]
+            .iter()
+            .map(|field| bulk_or_null(hash.as_ref().and_then(|hash| hash.get(field).cloned())))
+            .collect(),
+    ))
+}
+
+fn hdel(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let Some(hash) = db.hash_mut(args[1])? else {
+        return Ok(RespValue::Integer(0));
+    };
+    let removed = args[2..]
+        .iter()
+        .filter(|field| hash.remove(field).is_some())
+        .count();
+    db.remove_if_empty(args[1]);
+    Ok(RespValue::Integer(removed as i64))
+}
+
+fn hexists(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let exists = db
+        .hash_mut(args[1])?
+        .is_some_and(|hash| hash.contains_key(args[2]));
+    Ok(RespValue::Integer(exists as i64))
+}
+
+fn hlen(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let len = db.hash_mut(args[1])?.map_or(0, |hash| hash.len());
+    Ok(RespValue::Integer(len as i64))
+}
+
+fn hstrlen(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let len = db
+        .hash_mut(args[1])?
+        .and_then(|hash| hash.get(args[2]))
+        .map_or(0, Vec::len);
+    Ok(RespValue::Integer(len as i64))
+}
+
+fn hkeys(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let fields = match db.hash_mut(args[1])? {
+        Some(hash) => hash.keys().cloned().map(RespValue::BulkString).collect(),
+        None => vec![],
+    };
+    Ok(RespValue::Array(fields))
+}
+
+fn hvals(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let values = match db.hash_mut(args[1])? {
+        Some(hash) => hash.values().cloned().map(RespValue::BulkString).collect(),
+        None => vec![],
+    };
+    Ok(RespValue::Array(values))
+}
+
+fn hgetall(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let pairs = match db.hash_mut(args[1])? {
+        Some(hash) => hash
+            .iter()
+            .map(|(field, value)| {
+                (
+                    RespValue::BulkString(field.clone()),
+                    RespValue::BulkString(value.clone()),
+                )
+            })
+            .collect(),
+        None => vec![],
+    };
+    Ok(RespValue::Map(pairs))
+}
+
+fn hincrby(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let delta = parse_int::<i64>(args[3])?;
+    let mut db = executor.db();
+    let hash = db.hash_or_insert(args[1])?;
+    let current = match hash.get(args[2]) {
+        Some(value) => stored_int(value).ok_or_else(|| {
+            CommandError::Custom(String::from("ERR hash value is not an integer"))
+        })?,
+        None => 0,
+    };
+    let updated = current.checked_add(delta).ok_or_else(|| {
+        CommandError::Custom(String::from("ERR increment or decrement would overflow"))
+    })?;
+    hash.insert(args[2].to_vec(), updated.to_string().into_bytes());
+    Ok(RespValue::Integer(updated))
+}
+
+fn hincrbyfloat(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let delta = stored_float(args[3])
+        .ok_or_else(|| CommandError::Custom(String::from("ERR value is not a valid float")))?;
+    let mut db = executor.db();
+    let hash = db.hash_or_insert(args[1])?;
+    let (current, places) = match hash.get(args[2]) {
+        Some(value) => (
+            stored_float(value).ok_or_else(|| {
+                CommandError::Custom(String::from("ERR hash value is not a float"))
+            })?,
+            decimal_places(value),
+        ),
+        None => (0.0, 0),
+    };
+    let updated = current + delta;
+    if !updated.is_finite() {
+        return Err(CommandError::Custom(String::from(
+            "ERR increment would produce NaN or Infinity",
+        )));
+    }
+    let text = format_float(updated, places.max(decimal_places(args[3]))).into_bytes();
+    hash.insert(args[2].to_vec(), text.clone());
+    Ok(RespValue::BulkString(text))
+}
+
+/// Largest reply, in bytes, a negative `HRANDFIELD` count may build. Each
+/// pick is a fresh copy made while the database is locked, so the count
+/// alone cannot be trusted; this matches the default `proto-max-bulk-len`.
+const MAX_RANDOM_REPLY_BYTES: usize = 512 * 1024 * 1024;
+
+fn hrandfield(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let (count, with_values) = match args {
+        [_, _] => (None, false),
+        [_, _, count] => (Some(parse_int::<i64>(count)?), false),
+        [_, _, count, option] if option.eq_ignore_ascii_case(b"WITHVALUES") => {
+            (Some(parse_int::<i64>(count)?), true)
+        }
+        _ => return Err(CommandError::Syntax),
+    };
+    // Redis caps negative counts so the reply size cannot overflow.
+    let limit = if with_values { i64::MAX / 2 } else { i64::MAX };
+    if count.is_some_and(|count| count < -limit) {
+        return Err(CommandError::Custom(String::from(
+            "ERR value is out of range",
+        )));
+    }
+    let resp3 = executor.protocol() >= 3;
+    let mut db = executor.db();
+    let hash = db.hash_mut(args[1])?;
+    let Some(count) = count else {
+        let field = hash
+            .filter(|hash| !hash.is_empty())
+            .map(|hash| hash.keys().nth(random::below(hash.len())).cloned());
+        return Ok(bulk_or_null(field.flatten()));
+    };
+    let Some(hash) = hash else {
+        return Ok(RespValue::Array(vec![]));
+    };
+    let mut entries: Vec<(&Vec<u8>, &Vec<u8>)> = hash.iter().collect();
+    let values_per_pick = if with_values { 2 } else { 1 };
+    let picks = if count < 0 {
+        let picks = usize::try_from(count.unsigned_abs()).unwrap_or(usize::MAX);
+        let payload: usize = entries
+            .iter()
+            .map(|(field, value)| field.len() + if with_values { value.len() } else { 0 })
+            .sum();
+        let pick_size =
+            payload / entries.len() + values_per_pick * std::mem::size_of::<RespValue>();
+        if picks.saturating_mul(pick_size) > MAX_RANDOM_REPLY_BYTES {
+            return Err(CommandError::Custom(String::from(
+                "ERR value is out of range",
+            )));
+        }
+        picks
+    } else {
+        let count = (count as usize).min(entries.len());
+        for i in 0..count {
+            let j = i + random::below(entries.len() - i);
+            entries.swap(i, j);
+        }
+        count
+    };
+    let reply_len = if resp3 {
+        picks
+    } else {
+        picks * values_per_pick
+    };
+    let mut reply = Vec::with_capacity(reply_len);
+    for i in 0..picks {
+        // A negative count may return the same field more than once.
+        let (field, value) = if count < 0 {
+            entries[random::below(entries.len())]
+        } else {
+            entries[i]
+        };
+        let field = RespValue::BulkString(field.clone());
+        let value = RespValue::BulkString(value.clone());
+        match (with_values, resp3) {
+            (false, _) => reply.push(field),
+            (true, false) => reply.extend([field, value]),
+            (true, true) => reply.push(RespValue::Array(vec![field, value])),
+        }
+    }
+    Ok(RespValue::Array(reply))
+}
+
+fn hscan(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let cursor = parse_cursor(args[2])?;
+    let options = ScanOptions::parse(&args[3..], false)?;
+    let mut db = executor.db();
+    let Some(hash) = db.hash_mut(args[1])? else {
+        return Ok(scan_reply(0, vec![]));
+    };
+    let (next, page) = hash.page(cursor, options.count);
+    let items = page
+        .into_iter()
+        .filter(|field| options.matches(field))
+        .flat_map(|field| {
+            let value = hash.get(field).expect("scanned fields are in the hash");
+            [field.to_vec(), value.clone()]
+        })
+        .collect();
+    Ok(scan_reply(next, items))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internal::clock::SystemClock;
+    use crate::internal::cmd::{tests::command, CommandExecutor};
+    use crate::internal::db::{Store, DEFAULT_DATABASES};
+    use crate::internal::resp::RespValue;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn hash_commands() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let bulk = |s: &str| RespValue::BulkString(s.as_bytes().to_vec());
+        let int = RespValue::Integer;
+        let sorted = |reply: RespValue| match reply {
+            RespValue::Array(mut items) => {
+                items.sort_by_key(|item| format!("{:?}", item));
+                items
+            }
+            other => panic!("unexpected {:?}", other),
+        };
+
+        assert_eq!(
+            command(&["HSET", "h", "a", "1", "b", "2"]).accept(&mut client),
+            int(2)
+        );
+        assert_eq!(
+            command(&["HSET", "h", "b", "20", "c", "3"]).accept(&mut client),
+            int(1)
+        );
+        assert_eq!(
+            command(&["HSET", "h", "a"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR wrong number of arguments for 'hset' command"
+            ))
+        );
+        assert_eq!(
+            command(&["TYPE", "h"]).accept(&mut client),
+            RespValue::SimpleString(String::from("hash"))
+        );
+        assert_eq!(command(&["HGET", "h", "b"]).accept(&mut client), bulk("20"));
+        assert_eq!(
+            command(&["HGET", "h", "z"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["HMGET", "h", "a", "z"]).accept(&mut client),
+            RespValue::Array(vec![bulk("1"), RespValue::Null])
+        );
+        assert_eq!(
+            command(&["HMGET", "nosuch", "a"]).accept(&mut client),
+            RespValue::Array(vec![RespValue::Null])
+        );
+        assert_eq!(command(&["HLEN", "h"]).accept(&mut client), int(3));
+        assert_eq!(command(&["HEXISTS", "h", "c"]).accept(&mut client), int(1));
+        assert_eq!(command(&["HEXISTS", "h", "z"]).accept(&mut client), int(0));
+        assert_eq!(command(&["HSTRLEN", "h", "b"]).accept(&mut client), int(2));
+        assert_eq!(command(&["HSTRLEN", "h", "z"]).accept(&mut client), int(0));
+        assert_eq!(
+            sorted(command(&["HKEYS", "h"]).accept(&mut client)),
+            vec![bulk("a"), bulk("b"), bulk("c")]
+        );
+        assert_eq!(
+            sorted(command(&["HVALS", "h"]).accept(&mut client)),
+            vec![bulk("1"), bulk("20"), bulk("3")]
+        );
+        let RespValue::Map(mut pairs) = command(&["HGETALL", "h"]).accept(&mut client) else {
+            panic!("HGETALL should reply with a map");
+        };
+        pairs.sort_by_key(|pair| format!("{:?}", pair));
+        assert_eq!(
+            pairs,
+            vec![
+                (bulk("a"), bulk("1")),
+                (bulk("b"), bulk("20")),
+                (bulk("c"), bulk("3")),
+            ]
+        );
+        assert_eq!(
+            command(&["HGETALL", "nosuch"]).accept(&mut client),
+            RespValue::Map(vec![])
+        );
+
+        assert_eq!(
+            command(&["HSETNX", "h", "a", "x"]).accept(&mut client),
+            int(0)
+        );
+        assert_eq!(
+            command(&["HSETNX", "h", "d", "4"]).accept(&mut client),
+            int(1)
+        );
+        assert_eq!(
+            command(&["HINCRBY", "h", "d", "-10"]).accept(&mut client),
+            int(-6)
+        );
+        assert_eq!(
+            command(&["HINCRBY", "h", "new", "5"]).accept(&mut client),
+            int(5)
+        );
+        command(&["HSET", "h", "max", &i64::MAX.to_string()]).accept(&mut client);
+        assert_eq!(
+            command(&["HINCRBY", "h", "max", "1"]).accept(&mut client),
+            RespValue::Error(String::from("ERR increment or decrement would overflow"))
+        );
+        command(&["HSET", "h", "text", "abc"]).accept(&mut client);
+        assert_eq!(
+            command(&["HINCRBY", "h", "text", "1"]).accept(&mut client),
+            RespValue::Error(String::from("ERR hash value is not an integer"))
+        );
+        assert_eq!(
+            command(&["HINCRBY", "h", "d", "x"]).accept(&mut client),
+            RespValue::Error(String::from("ERR value is not an integer or out of range"))
+        );
+        assert_eq!(
+            command(&["HINCRBYFLOAT", "h", "a", "0.5"]).accept(&mut client),
+            bulk("1.5")
+        );
+        command(&["HSET", "h", "tenth", "0.1"]).accept(&mut client);
+        assert_eq!(
+            command(&["HINCRBYFLOAT", "h", "tenth", "0.2"]).accept(&mut client),
+            bulk("0.3")
+        );
+        assert_eq!(
+            command(&["HINCRBYFLOAT", "h", "text", "1"]).accept(&mut client),
+            RespValue::Error(String::from("ERR hash value is not a float"))
+        );
+        assert_eq!(
+            command(&["HINCRBYFLOAT", "h", "a", "nan"]).accept(&mut client),
+            RespValue::Error(String::from("ERR value is not a valid float"))
+        );
+
+        assert_eq!(
+            command(&["HDEL", "h", "a", "z", "b"]).accept(&mut client),
+            int(2)
+        );
+        command(&["HDEL", "h", "c", "d", "new", "max", "text", "tenth"]).accept(&mut client);
+        assert_eq!(command(&["EXISTS", "h"]).accept(&mut client), int(0));
+
+        command(&["HSET", "r", "a", "1", "b", "2", "c", "3"]).accept(&mut client);
+        let RespValue::BulkString(field) = command(&["HRANDFIELD", "r"]).accept(&mut client) else {
+            panic!("HRANDFIELD should reply with a field");
+        };
+        assert!([&b"a"[..], b"b", b"c"].contains(&field.as_slice()));
+        assert_eq!(
+            command(&["HRANDFIELD", "nosuch"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["HRANDFIELD", "nosuch", "3"]).accept(&mut client),
+            RespValue::Array(vec![])
+        );
+        let mut distinct = sorted(command(&["HRANDFIELD", "r", "2"]).accept(&mut client));
+        distinct.dedup();
+        assert_eq!(distinct.len(), 2);
+        assert_eq!(
+            sorted(command(&["HRANDFIELD", "r", "10"]).accept(&mut client)),
+            vec![bulk("a"), bulk("b"), bulk("c")]
+        );
+        assert_eq!(
+            sorted(command(&["HRANDFIELD", "r", "-7"]).accept(&mut client)).len(),
+            7
+        );
+        assert_eq!(
+            command(&["HRANDFIELD", "r", "0"]).accept(&mut client),
+            RespValue::Array(vec![])
+        );
+        let RespValue::Array(flat) =
+            command(&["HRANDFIELD", "r", "-2", "WITHVALUES"]).accept(&mut client)
+        else {
+            panic!("HRANDFIELD should reply with an array");
+        };
+        assert_eq!(flat.len(), 4);
+        command(&["HELLO", "3"]).accept(&mut client);
+        let RespValue::Array(nested) =
+            command(&["HRANDFIELD", "r", "1", "WITHVALUES"]).accept(&mut client)
+        else {
+            panic!("HRANDFIELD should reply with an array");
+        };
+        assert!(matches!(nested.as_slice(), [RespValue::Array(pair)] if pair.len() == 2));
+        command(&["HELLO", "2"]).accept(&mut client);
+        assert_eq!(
+            command(&["HRANDFIELD", "r", "1", "VALUES"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+        assert_eq!(
+            command(&["HRANDFIELD", "r", &i64::MIN.to_string()]).accept(&mut client),
+            RespValue::Error(String::from("ERR value is out of range"))
+        );
+        // A reply too big to build under the database lock is refused,
+        // whether or not its size would overflow.
+        let huge = (-i64::MAX).to_string();
+        for args in [
+            &["HRANDFIELD", "r", &huge][..],
+            &["HRANDFIELD", "r", "-100000000"],
+            &["HRANDFIELD", "r", "-100000000", "WITHVALUES"],
+        ] {
+            assert_eq!(
+                command(args).accept(&mut client),
+                RespValue::Error(String::from("ERR value is out of range"))
+            );
+        }
+        assert_eq!(
+            sorted(command(&["HRANDFIELD", "r", "-100000"]).accept(&mut client)).len(),
+            100000
+        );
+        assert_eq!(command(&["HLEN", "r"]).accept(&mut client), int(3));
+
+        for i in 0..50 {
+            command(&["HSET", "big", &format!("f{}", i), &i.to_string()]).accept(&mut client);
+        }
+        let mut seen = vec![];
+        let mut cursor = String::from("0");
+        loop {
+            let reply = command(&["HSCAN", "big", &cursor, "MATCH", "f1*", "COUNT", "7"])
+                .accept(&mut client);
+            let RespValue::Array(mut parts) = reply else {
+                panic!("unexpected {:?}", reply);
+            };
+            let RespValue::Array(items) = parts.pop().unwrap() else {
+                panic!("HSCAN items should be an array");
+            };
+            for pair in items.chunks(2) {
+                let (RespValue::BulkString(field), RespValue::BulkString(value)) =
+                    (&pair[0], &pair[1])
+                else {
+                    panic!("unexpected {:?}", pair);
+                };
+                assert_eq!(&field[1..], value.as_slice());
+                seen.push(field.clone());
+            }
+            cursor = match parts.pop().unwrap() {
+                RespValue::BulkString(next) => String::from_utf8(next).unwrap(),
+                other => panic!("unexpected {:?}", other),
+            };
+            if cursor == "0" {
+                break;
+            }
+        }
+        seen.sort();
+        let mut expected: Vec<Vec<u8>> = std::iter::once(1)
+            .chain(10..20)
+            .map(|i| format!("f{}", i).into_bytes())
+            .collect();
+        expected.sort();
+        assert_eq!(seen, expected);
+        assert_eq!(
+            command(&["HSCAN", "nosuch", "0"]).accept(&mut client),
+            RespValue::Array(vec![bulk("0"), RespValue::Array(vec![])])
+        );
+        assert_eq!(
+            command(&["HSCAN", "big", "0", "TYPE", "hash"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+
+        let wrongtype = RespValue::Error(String::from(
+            "WRONGTYPE Operation against a key holding the wrong kind of value",
+        ));
+        command(&["SET", "str", "v"]).accept(&mut client);
+        command(&["RPUSH", "lst", "v"]).accept(&mut client);
+        assert_eq!(
+            command(&["HSET", "str", "a", "1"]).accept(&mut client),
+            wrongtype
+        );
+        assert_eq!(
+            command(&["HGET", "lst", "a"]).accept(&mut client),
+            wrongtype
+        );
+        assert_eq!(
+            command(&["HSCAN", "str", "0"]).accept(&mut client),
+            wrongtype
+        );
+        assert_eq!(command(&["GET", "r"]).accept(&mut client), wrongtype);
+        assert_eq!(command(&["LPUSH", "r", "x"]).accept(&mut client), wrongtype);
+        assert_eq!(
+            command(&["APPEND", "r", "x"]).accept(&mut client),
+            wrongtype
+        );
+    }
+}
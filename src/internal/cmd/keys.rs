@@ -0,0 +1,879 @@
+use super::table::{CommandSpec, Flag, KeySpec};
+use super::{bulk_or_null, ok, parse_int, CommandExecutor, CommandResult};
+use crate::internal::error::CommandError;
+use crate::internal::glob::glob_match;
+use crate::internal::resp::RespValue;
+
+use std::sync::Arc;
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "expire",
+        arity: -3,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Sets the expiration time of a key in seconds.",
+        handler: expire,
+    },
+    CommandSpec {
+        name: "pexpire",
+        arity: -3,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Sets the expiration time of a key in milliseconds.",
+        handler: pexpire,
+    },
+    CommandSpec {
+        name: "expireat",
+        arity: -3,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Sets the expiration time of a key to a Unix timestamp.",
+        handler: expireat,
+    },
+    CommandSpec {
+        name: "pexpireat",
+        arity: -3,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Sets the expiration time of a key to a Unix milliseconds timestamp.",
+        handler: pexpireat,
+    },
+    CommandSpec {
+        name: "ttl",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Returns the expiration time in seconds of a key.",
+        handler: ttl,
+    },
+    CommandSpec {
+        name: "pttl",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Returns the expiration time in milliseconds of a key.",
+        handler: pttl,
+    },
+    CommandSpec {
+        name: "expiretime",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Returns the expiration time of a key as a Unix timestamp.",
+        handler: expiretime,
+    },
+    CommandSpec {
+        name: "pexpiretime",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Returns the expiration time of a key as a Unix milliseconds timestamp.",
+        handler: pexpiretime,
+    },
+    CommandSpec {
+        name: "persist",
+        arity: 2,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Removes the expiration time of a key.",
+        handler: persist,
+    },
+    CommandSpec {
+        name: "del",
+        arity: -2,
+        flags: &[Flag::Write],
+        keys: KeySpec::new(1, -1, 1),
+        group: "generic",
+        summary: "Deletes one or more keys.",
+        handler: del,
+    },
+    CommandSpec {
+        name: "unlink",
+        arity: -2,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::new(1, -1, 1),
+        group: "generic",
+        summary: "Asynchronously deletes one or more keys.",
+        handler: del,
+    },
+    CommandSpec {
+        name: "exists",
+        arity: -2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::new(1, -1, 1),
+        group: "generic",
+        summary: "Determines whether one or more keys exist.",
+        handler: exists,
+    },
+    CommandSpec {
+        name: "type",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "generic",
+        summary: "Determines the type of value stored at a key.",
+        handler: type_,
+    },
+    CommandSpec {
+        name: "rename",
+        arity: 3,
+        flags: &[Flag::Write],
+        keys: KeySpec::new(1, 2, 1),
+        group: "generic",
+        summary: "Renames a key and overwrites the destination.",
+        handler: rename,
+    },
+    CommandSpec {
+        name: "renamenx",
+        arity: 3,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::new(1, 2, 1),
+        group: "generic",
+        summary: "Renames a key only when the target key name doesn't exist.",
+        handler: renamenx,
+    },
+    CommandSpec {
+        name: "copy",
+        arity: -3,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::new(1, 2, 1),
+        group: "generic",
+        summary: "Copies the value of a key to a new key.",
+        handler: copy,
+    },
+    CommandSpec {
+        name: "randomkey",
+        arity: 1,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::NONE,
+        group: "generic",
+        summary: "Returns a random key name from the database.",
+        handler: randomkey,
+    },
+    CommandSpec {
+        name: "keys",
+        arity: 2,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::NONE,
+        group: "generic",
+        summary: "Returns all key names that match a pattern.",
+        handler: keys,
+    },
+    CommandSpec {
+        name: "scan",
+        arity: -2,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::NONE,
+        group: "generic",
+        summary: "Iterates over the key names in the database.",
+        handler: scan,
+    },
+];
+
+/// The `NX | XX | GT | LT` condition of the `EXPIRE` family.
+#[derive(Default)]
+struct ExpireCondition {
+    nx: bool,
+    xx: bool,
+    gt: bool,
+    lt: bool,
+}
+
+impl ExpireCondition {
+    fn parse(options: &[&[u8]]) -> Result<Self, CommandError> {
+        let mut condition = ExpireCondition::default();
+        for option in options {
+            match option.to_ascii_uppercase().as_slice() {
+                b"NX" => condition.nx = true,
+                b"XX" => condition.xx = true,
+                b"GT" => condition.gt = true,
+                b"LT" => condition.lt = true,
+                _ => {
+                    return Err(CommandError::Custom(format!(
+                        "ERR Unsupported option {}",
+                        String::from_utf8_lossy(option)
+                    )))
+                }
+            }
+        }
+        if condition.nx && (condition.xx || condition.gt || condition.lt) {
+            return Err(CommandError::Custom(String::from(
+                "ERR NX and XX, GT or LT options at the same time are not compatible",
+            )));
+        }
+        if condition.gt && condition.lt {
+            return Err(CommandError::Custom(String::from(
+                "ERR GT and LT options at the same time are not compatible",
+            )));
+        }
+        Ok(condition)
+    }
+
+    /// Whether a key whose deadline is `current` (`None` meaning it never
+    /// expires) may take the deadline `new`.
+    fn allows(&self, current: Option<i64>, new: i64) -> bool {
+        match current {
+            None => !(self.xx || self.gt),
+            Some(current) => {
+                !(self.nx || (self.gt && new <= current) || (self.lt && new >= current))
+            }
+        }
+    }
+}
+
+/// Shared body of `EXPIRE`, `PEXPIRE`, `EXPIREAT` and `PEXPIREAT`:
+/// `args[2]` is in `unit_ms` milliseconds and relative to `base_ms`.
+fn expire_generic(
+    executor: &mut CommandExecutor,
+    args: &[&[u8]],
+    name: &str,
+    base_ms: i64,
+    unit_ms: i64,
+) -> CommandResult {
+    let amount = parse_int::<i64>(args[2])?;
+    let condition = ExpireCondition::parse(&args[3..])?;
+    let deadline_ms = amount
+        .checked_mul(unit_ms)
+        .and_then(|ms| ms.checked_add(base_ms))
+        .ok_or_else(|| CommandError::InvalidExpireTime(String::from(name)))?;
+
+    let clock = Arc::clone(&executor.clock);
+    let now_ms = clock.unix_millis();
+    let mut db = executor.db();
+    let Some(current) = db.expiry_time(args[1]) else {
+        return Ok(RespValue::Integer(0));
+    };
+    if !condition.allows(current.map(|at| clock.unix_millis_at(at)), deadline_ms) {
+        return Ok(RespValue::Integer(0));
+    }
+    if deadline_ms <= now_ms {
+        db.remove(args[1]);
+    } else {
+        db.set_expiry_time(args[1], Some(clock.instant_at_unix_millis(deadline_ms)));
+    }
+    Ok(RespValue::Integer(1))
+}
+
+fn expire(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let now_ms = executor.clock.unix_millis();
+    expire_generic(executor, args, "expire", now_ms, 1000)
+}
+
+fn pexpire(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let now_ms = executor.clock.unix_millis();
+    expire_generic(executor, args, "pexpire", now_ms, 1)
+}
+
+fn expireat(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    expire_generic(executor, args, "expireat", 0, 1000)
+}
+
+fn pexpireat(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    expire_generic(executor, args, "pexpireat", 0, 1)
+}
+
+/// Replies `-2` for a missing key, `-1` for a key without a TTL, and
+/// otherwise `reply(deadline_ms, now_ms)`.
+fn ttl_generic(
+    executor: &mut CommandExecutor,
+    key: &[u8],
+    reply: fn(i64, i64) -> i64,
+) -> CommandResult {
+    let clock = &executor.clock;
+    let now_ms = clock.unix_millis();
+    Ok(RespValue::Integer(match executor.db().expiry_time(key) {
+        None => -2,
+        Some(None) => -1,
+        Some(Some(deadline)) => reply(clock.unix_millis_at(deadline), now_ms),
+    }))
+}
+
+fn ttl(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    ttl_generic(executor, args[1], |deadline, now| {
+        ((deadline - now).max(0) + 500) / 1000
+    })
+}
+
+fn pttl(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    ttl_generic(executor, args[1], |deadline, now| (deadline - now).max(0))
+}
+
+fn expiretime(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    ttl_generic(executor, args[1], |deadline, _| deadline / 1000)
+}
+
+fn pexpiretime(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    ttl_generic(executor, args[1], |deadline, _| deadline)
+}
+
+fn persist(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    Ok(RespValue::Integer(match db.expiry_time(args[1]) {
+        Some(Some(_)) => {
+            db.set_expiry_time(args[1], None);
+            1
+        }
+        _ => 0,
+    }))
+}
+
+fn del(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let removed = args[1..].iter().filter(|key| db.remove(key)).count();
+    Ok(RespValue::Integer(removed as i64))
+}
+
+fn exists(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let found = args[1..].iter().filter(|key| db.contains_key(key)).count();
+    Ok(RespValue::Integer(found as i64))
+}
+
+fn type_(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let name = executor.db().type_of(args[1]).unwrap_or("none");
+    Ok(RespValue::SimpleString(String::from(name)))
+}
+
+/// Moves `args[1]` to `args[2]` along with its TTL; with `nx` an existing
+/// destination makes it a no-op. Replies whether the key was moved.
+fn rename_generic(
+    executor: &mut CommandExecutor,
+    args: &[&[u8]],
+    nx: bool,
+) -> Result<bool, CommandError> {
+    let mut db = executor.db();
+    if !db.contains_key(args[1]) {
+        return Err(CommandError::Custom(String::from("ERR no such key")));
+    }
+    if args[1] == args[2] {
+        return Ok(!nx);
+    }
+    if nx && db.contains_key(args[2]) {
+        return Ok(false);
+    }
+    let entry = db.take(args[1]).expect("source key was just checked");
+    db.insert(args[2].to_vec(), entry);
+    Ok(true)
+}
+
+fn rename(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    rename_generic(executor, args, false)?;
+    Ok(ok())
+}
+
+fn renamenx(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let renamed = rename_generic(executor, args, true)?;
+    Ok(RespValue::Integer(renamed as i64))
+}
+
+fn copy(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut target_db = executor.session.db;
+    let mut replace = false;
+    let mut idx = 3;
+    while idx < args.len() {
+        match args[idx].to_ascii_uppercase().as_slice() {
+            b"REPLACE" => replace = true,
+            b"DB" if idx + 1 < args.len() => {
+                let index = parse_int::<i64>(args[idx + 1])?;
+                if index < 0 || index as usize >= executor.store.num_databases() {
+                    return Err(CommandError::Custom(String::from(
+                        "ERR DB index is out of range",
+                    )));
+                }
+                target_db = index as usize;
+                idx += 1;
+            }
+            _ => return Err(CommandError::Syntax),
+        }
+        idx += 1;
+    }
+    if target_db == executor.session.db && args[1] == args[2] {
+        return Err(CommandError::Custom(String::from(
+            "ERR source and destination objects are the same",
+        )));
+    }
+
+    // Only one database is locked at a time, so copies in opposite
+    // directions between two databases cannot deadlock.
+    let Some(entry) = executor.db().entry(args[1]) else {
+        return Ok(RespValue::Integer(0));
+    };
+    let mut db = executor.db_at(target_db);
+    if !replace && db.contains_key(args[2]) {
+        return Ok(RespValue::Integer(0));
+    }
+    db.insert(args[2].to_vec(), entry);
+    Ok(RespValue::Integer(1))
+}
+
+fn randomkey(executor: &mut CommandExecutor, _args: &[&[u8]]) -> CommandResult {
+    Ok(bulk_or_null(executor.db().random_key()))
+}
+
+fn keys(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    Ok(RespValue::Array(
+        executor
+            .db()
+            .keys()
+            .into_iter()
+            .filter(|key| glob_match(args[1], key))
+            .map(RespValue::BulkString)
+            .collect(),
+    ))
+}
+
+/// `MATCH`, `COUNT` and `TYPE` options of the `SCAN` family; only `SCAN`
+/// itself accepts `TYPE`.
+pub struct ScanOptions<'a> {
+    pub pattern: Option<&'a [u8]>,
+    pub count: usize,
+    pub type_name: Option<String>,
+}
+
+impl<'a> ScanOptions<'a> {
+    pub fn parse(args: &[&'a [u8]], allow_type: bool) -> Result<Self, CommandError> {
+        let mut options = ScanOptions {
+            pattern: None,
+            count: 10,
+            type_name: None,
+        };
+        let mut idx = 0;
+        while idx < args.len() {
+            let value = *args.get(idx + 1).ok_or(CommandError::Syntax)?;
+            match args[idx].to_ascii_uppercase().as_slice() {
+                b"MATCH" => options.pattern = Some(value),
+                b"COUNT" => {
+                    let count = parse_int::<i64>(value)?;
+                    if count < 1 {
+                        return Err(CommandError::Syntax);
+                    }
+                    options.count = count as usize;
+                }
+                b"TYPE" if allow_type => {
+                    options.type_name = Some(String::from_utf8_lossy(value).to_lowercase())
+                }
+                _ => return Err(CommandError::Syntax),
+            }
+            idx += 2;
+        }
+        Ok(options)
+    }
+
+    pub fn matches(&self, item: &[u8]) -> bool {
+        self.pattern.is_none_or(|pattern| glob_match(pattern, item))
+    }
+}
+
+pub fn parse_cursor(arg: &[u8]) -> Result<u64, CommandError> {
+    parse_int::<u64>(arg).map_err(|_| CommandError::Custom(String::from("ERR invalid cursor")))
+}
+
+/// The `[cursor, [items...]]` reply of the `SCAN` family.
+pub fn scan_reply(cursor: u64, items: Vec<Vec<u8>>) -> RespValue {
+    RespValue::Array(vec![
+        RespValue::BulkString(cursor.to_string().into_bytes()),
+        RespValue::Array(items.into_iter().map(RespValue::BulkString).collect()),
+    ])
+}
+
+fn scan(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let cursor = parse_cursor(args[1])?;
+    let options = ScanOptions::parse(&args[2..], true)?;
+    let mut db = executor.db();
+    let (next, page) = db.scan(cursor, options.count);
+    let keys = page
+        .into_iter()
+        .filter(|key| options.matches(key))
+        .filter(|key| match options.type_name {
+            Some(ref wanted) => db.type_of(key) == Some(wanted.as_str()),
+            None => true,
+        })
+        .collect();
+    Ok(scan_reply(next, keys))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internal::clock::{Clock, ManualClock, SystemClock};
+    use crate::internal::cmd::{tests::command, CommandExecutor};
+    use crate::internal::db::{Store, DEFAULT_DATABASES};
+    use crate::internal::resp::RespValue;
+
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn expire_family_on_every_type() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        command(&["SET", "str", "v"]).accept(&mut client);
+        command(&["RPUSH", "lst", "a"]).accept(&mut client);
+
+        assert_eq!(
+            command(&["TTL", "missing"]).accept(&mut client),
+            RespValue::Integer(-2)
+        );
+        assert_eq!(
+            command(&["TTL", "str"]).accept(&mut client),
+            RespValue::Integer(-1)
+        );
+        assert_eq!(
+            command(&["EXPIRETIME", "lst"]).accept(&mut client),
+            RespValue::Integer(-1)
+        );
+
+        for key in ["str", "lst"] {
+            assert_eq!(
+                command(&["EXPIRE", key, "100"]).accept(&mut client),
+                RespValue::Integer(1)
+            );
+            assert_eq!(
+                command(&["TTL", key]).accept(&mut client),
+                RespValue::Integer(100)
+            );
+            assert_eq!(
+                command(&["PERSIST", key]).accept(&mut client),
+                RespValue::Integer(1)
+            );
+            assert_eq!(
+                command(&["PTTL", key]).accept(&mut client),
+                RespValue::Integer(-1)
+            );
+        }
+
+        assert_eq!(
+            command(&["PEXPIREAT", "str", "4102444800000"]).accept(&mut client),
+            RespValue::Integer(1)
+        );
+        assert_eq!(
+            command(&["EXPIRETIME", "str"]).accept(&mut client),
+            RespValue::Integer(4102444800)
+        );
+        assert_eq!(
+            command(&["EXPIREAT", "str", "1"]).accept(&mut client),
+            RespValue::Integer(1)
+        );
+        assert_eq!(
+            command(&["GET", "str"]).accept(&mut client),
+            RespValue::Null
+        );
+    }
+
+    #[test]
+    fn expire_conditions() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        command(&["SET", "k", "v"]).accept(&mut client);
+
+        let zero = RespValue::Integer(0);
+        let one = RespValue::Integer(1);
+        assert_eq!(
+            command(&["EXPIRE", "k", "100", "XX"]).accept(&mut client),
+            zero
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "100", "GT"]).accept(&mut client),
+            zero
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "100", "LT"]).accept(&mut client),
+            one
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "50", "NX"]).accept(&mut client),
+            zero
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "50", "GT"]).accept(&mut client),
+            zero
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "200", "GT"]).accept(&mut client),
+            one
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "300", "LT"]).accept(&mut client),
+            zero
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "10", "xx", "lt"]).accept(&mut client),
+            one
+        );
+        assert_eq!(
+            command(&["TTL", "k"]).accept(&mut client),
+            RespValue::Integer(10)
+        );
+        assert_eq!(
+            command(&["EXPIRE", "missing", "10"]).accept(&mut client),
+            zero
+        );
+
+        assert_eq!(
+            command(&["EXPIRE", "k", "10", "NX", "XX"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR NX and XX, GT or LT options at the same time are not compatible"
+            ))
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "10", "GT", "LT"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR GT and LT options at the same time are not compatible"
+            ))
+        );
+        assert_eq!(
+            command(&["EXPIRE", "k", "9223372036854775807"]).accept(&mut client),
+            RespValue::Error(String::from("ERR invalid expire time in 'expire' command"))
+        );
+    }
+
+    #[test]
+    fn ttl_follows_the_injected_clock() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let clock = Arc::new(ManualClock::new());
+        let mut client = CommandExecutor::new(store, clock.clone());
+        command(&["SET", "session", "v", "PX", "1500"]).accept(&mut client);
+        command(&["RPUSH", "queue", "a"]).accept(&mut client);
+        command(&["EXPIRE", "queue", "10"]).accept(&mut client);
+
+        clock.advance(Duration::from_millis(1000));
+        assert_eq!(
+            command(&["PTTL", "session"]).accept(&mut client),
+            RespValue::Integer(500)
+        );
+        assert_eq!(
+            command(&["GET", "session"]).accept(&mut client),
+            RespValue::BulkString(b"v".to_vec())
+        );
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(
+            command(&["GET", "session"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["TTL", "queue"]).accept(&mut client),
+            RespValue::Integer(9)
+        );
+    }
+
+    #[test]
+    fn wall_clock_jumps_do_not_change_relative_ttls() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let clock = Arc::new(ManualClock::new());
+        let mut client = CommandExecutor::new(store, clock.clone());
+        command(&["SET", "k", "v", "EX", "100"]).accept(&mut client);
+        let expire_at = clock.unix_millis() + 50_000;
+        command(&["SET", "abs", "v"]).accept(&mut client);
+        command(&["PEXPIREAT", "abs", &expire_at.to_string()]).accept(&mut client);
+
+        clock.jump_wall_clock(-3_600_000);
+        assert_eq!(
+            command(&["TTL", "k"]).accept(&mut client),
+            RespValue::Integer(100)
+        );
+        clock.jump_wall_clock(7_200_000);
+        assert_eq!(
+            command(&["GET", "k"]).accept(&mut client),
+            RespValue::BulkString(b"v".to_vec())
+        );
+        assert_eq!(
+            command(&["TTL", "abs"]).accept(&mut client),
+            RespValue::Integer(50)
+        );
+
+        clock.advance(Duration::from_secs(50));
+        assert_eq!(
+            command(&["TTL", "abs"]).accept(&mut client),
+            RespValue::Integer(-2)
+        );
+    }
+
+    #[test]
+    fn generic_key_commands() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let clock = Arc::new(ManualClock::new());
+        let mut client = CommandExecutor::new(Arc::clone(&store), clock.clone());
+        let mut other = CommandExecutor::new(store, clock.clone());
+        let int = RespValue::Integer;
+        let status = |s: &str| RespValue::SimpleString(String::from(s));
+        let bulk = |v: &str| RespValue::BulkString(v.as_bytes().to_vec());
+
+        command(&["MSET", "a", "1", "b", "2", "c", "3"]).accept(&mut client);
+        command(&["RPUSH", "l", "x"]).accept(&mut client);
+        assert_eq!(command(&["DBSIZE"]).accept(&mut client), int(4));
+        assert_eq!(
+            command(&["TYPE", "a"]).accept(&mut client),
+            status("string")
+        );
+        assert_eq!(command(&["TYPE", "l"]).accept(&mut client), status("list"));
+        assert_eq!(command(&["TYPE", "zz"]).accept(&mut client), status("none"));
+        assert_eq!(
+            command(&["EXISTS", "a", "a", "l", "zz"]).accept(&mut client),
+            int(3)
+        );
+        assert_eq!(
+            command(&["DEL", "a", "zz", "l"]).accept(&mut client),
+            int(2)
+        );
+        assert_eq!(command(&["UNLINK", "b"]).accept(&mut client), int(1));
+        command(&["SET", "gone", "v", "PX", "10"]).accept(&mut client);
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(command(&["DEL", "gone"]).accept(&mut client), int(0));
+        assert_eq!(command(&["RANDOMKEY"]).accept(&mut client), bulk("c"));
+
+        command(&["SET", "src", "v", "EX", "100"]).accept(&mut client);
+        assert_eq!(
+            command(&["RENAME", "src", "dst"]).accept(&mut client),
+            status("OK")
+        );
+        assert_eq!(command(&["TTL", "dst"]).accept(&mut client), int(100));
+        assert_eq!(command(&["EXISTS", "src"]).accept(&mut client), int(0));
+        assert_eq!(
+            command(&["RENAME", "src", "dst"]).accept(&mut client),
+            RespValue::Error(String::from("ERR no such key"))
+        );
+        assert_eq!(
+            command(&["RENAMENX", "dst", "c"]).accept(&mut client),
+            int(0)
+        );
+        assert_eq!(
+            command(&["RENAMENX", "dst", "d"]).accept(&mut client),
+            int(1)
+        );
+        assert_eq!(
+            command(&["RENAME", "d", "d"]).accept(&mut client),
+            status("OK")
+        );
+
+        assert_eq!(command(&["COPY", "d", "c"]).accept(&mut client), int(0));
+        assert_eq!(
+            command(&["COPY", "d", "c", "REPLACE"]).accept(&mut client),
+            int(1)
+        );
+        assert_eq!(command(&["GET", "c"]).accept(&mut client), bulk("v"));
+        assert_eq!(command(&["TTL", "c"]).accept(&mut client), int(100));
+        assert_eq!(
+            command(&["COPY", "d", "d", "DB", "1"]).accept(&mut client),
+            int(1)
+        );
+        assert_eq!(
+            command(&["COPY", "d", "d"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR source and destination objects are the same"
+            ))
+        );
+        assert_eq!(
+            command(&["COPY", "d", "d", "DB", "16"]).accept(&mut client),
+            RespValue::Error(String::from("ERR DB index is out of range"))
+        );
+        command(&["SELECT", "1"]).accept(&mut other);
+        assert_eq!(command(&["GET", "d"]).accept(&mut other), bulk("v"));
+
+        assert_eq!(command(&["FLUSHDB"]).accept(&mut client), status("OK"));
+        assert_eq!(command(&["DBSIZE"]).accept(&mut client), int(0));
+        assert_eq!(command(&["RANDOMKEY"]).accept(&mut client), RespValue::Null);
+        assert_eq!(command(&["DBSIZE"]).accept(&mut other), int(1));
+        assert_eq!(
+            command(&["FLUSHALL", "ASYNC"]).accept(&mut client),
+            status("OK")
+        );
+        assert_eq!(command(&["DBSIZE"]).accept(&mut other), int(0));
+        assert_eq!(
+            command(&["FLUSHDB", "LATER"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+    }
+
+    #[test]
+    fn keys_and_scan() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        for i in 0..100 {
+            command(&["SET", &format!("user:{}", i), "v"]).accept(&mut client);
+            command(&["RPUSH", &format!("queue:{}", i), "v"]).accept(&mut client);
+        }
+        command(&["SET", "h*llo", "v"]).accept(&mut client);
+
+        let sorted_keys = |reply: RespValue| match reply {
+            RespValue::Array(items) => {
+                let mut keys: Vec<Vec<u8>> = items
+                    .into_iter()
+                    .map(|item| match item {
+                        RespValue::BulkString(key) => key,
+                        other => panic!("unexpected {:?}", other),
+                    })
+                    .collect();
+                keys.sort();
+                keys
+            }
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(
+            sorted_keys(command(&["KEYS", "user:1?"]).accept(&mut client)),
+            (10..20)
+                .map(|i| format!("user:{}", i).into_bytes())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sorted_keys(command(&["KEYS", "queue:[^0-8]"]).accept(&mut client)),
+            vec![b"queue:9".to_vec()]
+        );
+        assert_eq!(
+            sorted_keys(command(&["KEYS", "h\\*llo"]).accept(&mut client)),
+            vec![b"h*llo".to_vec()]
+        );
+        assert_eq!(
+            sorted_keys(command(&["KEYS", "*"]).accept(&mut client)).len(),
+            201
+        );
+
+        let mut cursor = String::from("0");
+        let mut seen = vec![];
+        loop {
+            let reply = command(&[
+                "SCAN", &cursor, "MATCH", "user:*", "COUNT", "15", "TYPE", "string",
+            ])
+            .accept(&mut client);
+            let RespValue::Array(mut parts) = reply else {
+                panic!("unexpected {:?}", reply);
+            };
+            seen.extend(sorted_keys(parts.pop().unwrap()));
+            cursor = match parts.pop().unwrap() {
+                RespValue::BulkString(next) => String::from_utf8(next).unwrap(),
+                other => panic!("unexpected {:?}", other),
+            };
+            if cursor == "0" {
+                break;
+            }
+        }
+        seen.sort();
+        let mut expected: Vec<Vec<u8>> = (0..100)
+            .map(|i| format!("user:{}", i).into_bytes())
+            .collect();
+        expected.sort();
+        assert_eq!(seen, expected);
+
+        assert_eq!(
+            command(&["SCAN", "abc"]).accept(&mut client),
+            RespValue::Error(String::from("ERR invalid cursor"))
+        );
+        assert_eq!(
+            command(&["SCAN", "0", "COUNT", "0"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+        assert_eq!(
+            command(&["SCAN", "0", "MATCH"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+    }
+}
@@ -0,0 +1,1154 @@
+use super::table::{CommandSpec, Flag, KeySpec};
+use super::{bulk_or_null, ok, parse_int, CommandExecutor, CommandResult};
+use crate::internal::blocking::{ListWait, Served};
+use crate::internal::error::CommandError;
+use crate::internal::resp::RespValue;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "rpush",
+        arity: -3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Appends one or more elements to a list. Creates the key if it doesn't exist.",
+        handler: rpush,
+    },
+    CommandSpec {
+        name: "lpush",
+        arity: -3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Prepends one or more elements to a list. Creates the key if it doesn't exist.",
+        handler: lpush,
+    },
+    CommandSpec {
+        name: "lpushx",
+        arity: -3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Prepends one or more elements to a list only when the list exists.",
+        handler: lpushx,
+    },
+    CommandSpec {
+        name: "rpushx",
+        arity: -3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Appends an element to a list only when the list exists.",
+        handler: rpushx,
+    },
+    CommandSpec {
+        name: "lpop",
+        arity: -2,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Returns the first elements in a list after removing it. Deletes the list if the last element was popped.",
+        handler: lpop,
+    },
+    CommandSpec {
+        name: "rpop",
+        arity: -2,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Returns and removes the last elements of a list. Deletes the list if the last element was popped.",
+        handler: rpop,
+    },
+    CommandSpec {
+        name: "llen",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Returns the length of a list.",
+        handler: llen,
+    },
+    CommandSpec {
+        name: "lrange",
+        arity: 4,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Returns a range of elements from a list.",
+        handler: lrange,
+    },
+    CommandSpec {
+        name: "lindex",
+        arity: 3,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Returns an element from a list by its index.",
+        handler: lindex,
+    },
+    CommandSpec {
+        name: "lset",
+        arity: 4,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Sets the value of an element in a list by its index.",
+        handler: lset,
+    },
+    CommandSpec {
+        name: "linsert",
+        arity: 5,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Inserts an element before or after another element in a list.",
+        handler: linsert,
+    },
+    CommandSpec {
+        name: "lrem",
+        arity: 4,
+        flags: &[Flag::Write],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Removes elements from a list. Deletes the list if the last element was removed.",
+        handler: lrem,
+    },
+    CommandSpec {
+        name: "ltrim",
+        arity: 4,
+        flags: &[Flag::Write],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Removes elements from both ends a list. Deletes the list if all elements were trimmed.",
+        handler: ltrim,
+    },
+    CommandSpec {
+        name: "lpos",
+        arity: -3,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "list",
+        summary: "Returns the index of matching elements in a list.",
+        handler: lpos,
+    },
+    CommandSpec {
+        name: "lmove",
+        arity: 5,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::new(1, 2, 1),
+        group: "list",
+        summary: "Returns an element after popping it from one list and pushing it to another. Deletes the list if the last element was moved.",
+        handler: lmove,
+    },
+    CommandSpec {
+        name: "rpoplpush",
+        arity: 3,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::new(1, 2, 1),
+        group: "list",
+        summary: "Returns the last element of a list after removing and pushing it to another list. Deletes the list if the last element was popped.",
+        handler: rpoplpush,
+    },
+    CommandSpec {
+        name: "lmpop",
+        arity: -4,
+        flags: &[Flag::Write],
+        keys: KeySpec::numkeys(1),
+        group: "list",
+        summary: "Returns multiple elements from a list after removing them. Deletes the list if the last element was popped.",
+        handler: lmpop,
+    },
+    CommandSpec {
+        name: "blpop",
+        arity: -3,
+        flags: &[Flag::Write, Flag::Blocking],
+        keys: KeySpec::new(1, -2, 1),
+        group: "list",
+        summary: "Removes and returns the first element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        handler: blpop,
+    },
+    CommandSpec {
+        name: "brpop",
+        arity: -3,
+        flags: &[Flag::Write, Flag::Blocking],
+        keys: KeySpec::new(1, -2, 1),
+        group: "list",
+        summary: "Removes and returns the last element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        handler: brpop,
+    },
+    CommandSpec {
+        name: "blmove",
+        arity: 6,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Blocking],
+        keys: KeySpec::new(1, 2, 1),
+        group: "list",
+        summary: "Pops an element from a list, pushes it to another list and returns it. Blocks until an element is available otherwise. Deletes the list if the last element was moved.",
+        handler: blmove,
+    },
+];
+
+fn rpush(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let buffer = args[2..].iter().map(|e| e.to_vec()).collect();
+    let lst_len = executor.db().rpush(args[1], buffer)?;
+    Ok(RespValue::Integer(lst_len))
+}
+
+fn lpush(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let buffer = args[2..].iter().map(|e| e.to_vec()).collect();
+    let lst_len = executor.db().lpush(args[1], buffer)?;
+    Ok(RespValue::Integer(lst_len))
+}
+
+/// Shared body of `LPUSHX` and `RPUSHX`: pushes only onto an existing list.
+fn pushx(executor: &mut CommandExecutor, args: &[&[u8]], to_head: bool) -> CommandResult {
+    let mut db = executor.db();
+    let Some(list) = db.list_mut(args[1])? else {
+        return Ok(RespValue::Integer(0));
+    };
+    for element in &args[2..] {
+        if to_head {
+            list.push_front(element.to_vec());
+        } else {
+            list.push_back(element.to_vec());
+        }
+    }
+    Ok(RespValue::Integer(list.len() as i64))
+}
+
+fn lpushx(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    pushx(executor, args, true)
+}
+
+fn rpushx(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    pushx(executor, args, false)
+}
+
+fn out_of_range(what: &str) -> CommandError {
+    CommandError::Custom(format!("ERR value is out of range, {}", what))
+}
+
+/// Shared body of `LPOP` and `RPOP`: a single element, or with a count an
+/// array (a null array when the key is missing).
+fn pop_generic(
+    executor: &mut CommandExecutor,
+    args: &[&[u8]],
+    name: &str,
+    from_head: bool,
+) -> CommandResult {
+    let count = match args {
+        [_, _] => None,
+        [_, _, count] => {
+            let count = parse_int::<i64>(count).map_err(|_| out_of_range("must be positive"))?;
+            if count < 0 {
+                return Err(out_of_range("must be positive"));
+            }
+            Some(count as usize)
+        }
+        _ => return Err(CommandError::WrongArity(String::from(name))),
+    };
+    let popped = executor.db().pop(args[1], from_head, count.unwrap_or(1))?;
+    Ok(match (popped, count) {
+        (None, Some(_)) => RespValue::NullArray,
+        (popped, None) => bulk_or_null(popped.and_then(|mut popped| popped.pop())),
+        (Some(popped), Some(_)) => {
+            RespValue::Array(popped.into_iter().map(RespValue::BulkString).collect())
+        }
+    })
+}
+
+fn lpop(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    pop_generic(executor, args, "lpop", true)
+}
+
+fn rpop(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    pop_generic(executor, args, "rpop", false)
+}
+
+fn llen(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    Ok(RespValue::Integer(executor.db().llen(args[1])?))
+}
+
+fn lrange(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let start_idx = parse_int::<i64>(args[2])?;
+    let end_idx = parse_int::<i64>(args[3])?;
+    let buffer = executor.db().lrange(args[1], start_idx, end_idx)?;
+    Ok(RespValue::Array(
+        buffer.into_iter().map(RespValue::BulkString).collect(),
+    ))
+}
+
+/// Resolves a possibly negative list index; `None` if it is out of range.
+fn list_index(len: usize, index: i64) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+fn lindex(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let index = parse_int::<i64>(args[2])?;
+    let mut db = executor.db();
+    let element = db
+        .list_mut(args[1])?
+        .and_then(|list| list_index(list.len(), index).map(|i| list[i].clone()));
+    Ok(bulk_or_null(element))
+}
+
+fn lset(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let index = parse_int::<i64>(args[2])?;
+    let mut db = executor.db();
+    let list = db
+        .list_mut(args[1])?
+        .ok_or_else(|| CommandError::Custom(String::from("ERR no such key")))?;
+    let index = list_index(list.len(), index)
+        .ok_or_else(|| CommandError::Custom(String::from("ERR index out of range")))?;
+    list[index] = args[3].to_vec();
+    Ok(ok())
+}
+
+fn linsert(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let after = match args[2].to_ascii_uppercase().as_slice() {
+        b"BEFORE" => false,
+        b"AFTER" => true,
+        _ => return Err(CommandError::Syntax),
+    };
+    let mut db = executor.db();
+    let Some(list) = db.list_mut(args[1])? else {
+        return Ok(RespValue::Integer(0));
+    };
+    let Some(pivot) = list
+        .iter()
+        .position(|element| element.as_slice() == args[3])
+    else {
+        return Ok(RespValue::Integer(-1));
+    };
+    list.insert(pivot + after as usize, args[4].to_vec());
+    Ok(RespValue::Integer(list.len() as i64))
+}
+
+fn lrem(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let count = parse_int::<i64>(args[2])?;
+    let limit = if count == 0 {
+        usize::MAX
+    } else {
+        count.unsigned_abs() as usize
+    };
+    let mut db = executor.db();
+    let Some(list) = db.list_mut(args[1])? else {
+        return Ok(RespValue::Integer(0));
+    };
+    let mut removed = 0;
+    if count >= 0 {
+        list.retain(|element| {
+            let hit = removed < limit && element.as_slice() == args[3];
+            removed += usize::from(hit);
+            !hit
+        });
+    } else {
+        // Pop from the tail until enough matches are gone, then put back
+        // the elements that were kept, so each element moves at most once.
+        let mut kept = VecDeque::new();
+        while removed < limit {
+            let Some(element) = list.pop_back() else {
+                break;
+            };
+            if element.as_slice() == args[3] {
+                removed += 1;
+            } else {
+                kept.push_front(element);
+            }
+        }
+        list.extend(kept);
+    }
+    db.remove_if_empty(args[1]);
+    Ok(RespValue::Integer(removed as i64))
+}
+
+fn ltrim(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let start = parse_int::<i64>(args[2])?;
+    let end = parse_int::<i64>(args[3])?;
+    let mut db = executor.db();
+    let Some(list) = db.list_mut(args[1])? else {
+        return Ok(ok());
+    };
+    let len = list.len() as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let end = if end < 0 { len + end } else { end.min(len - 1) };
+    if start > end || start >= len {
+        list.clear();
+    } else {
+        list.truncate(end as usize + 1);
+        list.drain(..start as usize);
+    }
+    db.remove_if_empty(args[1]);
+    Ok(ok())
+}
+
+fn lpos(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut rank = 1i64;
+    let mut count = None;
+    let mut max_len = 0usize;
+    let mut idx = 3;
+    while idx < args.len() {
+        let value = *args.get(idx + 1).ok_or(CommandError::Syntax)?;
+        match args[idx].to_ascii_uppercase().as_slice() {
+            b"RANK" => {
+                rank = parse_int::<i64>(value)?;
+                if rank == 0 {
+                    return Err(CommandError::Custom(String::from(
+                        "ERR RANK can't be zero: use 1 to start from the first match, \
+                         2 from the second ... or use negative to start from the last match",
+                    )));
+                }
+                if rank == i64::MIN {
+                    return Err(out_of_range(
+                        "value must between -9223372036854775807 and 9223372036854775807",
+                    ));
+                }
+            }
+            b"COUNT" => {
+                let value = parse_int::<i64>(value)?;
+                if value < 0 {
+                    return Err(CommandError::Custom(String::from(
+                        "ERR COUNT can't be negative",
+                    )));
+                }
+                count = Some(value as usize);
+            }
+            b"MAXLEN" => {
+                let value = parse_int::<i64>(value)?;
+                if value < 0 {
+                    return Err(CommandError::Custom(String::from(
+                        "ERR MAXLEN can't be negative",
+                    )));
+                }
+                max_len = value as usize;
+            }
+            _ => return Err(CommandError::Syntax),
+        }
+        idx += 2;
+    }
+
+    let mut db = executor.db();
+    let positions: Vec<usize> = match db.list_mut(args[1])? {
+        None => vec![],
+        Some(list) => {
+            let scanned = if max_len == 0 {
+                list.len()
+            } else {
+                max_len.min(list.len())
+            };
+            let indexes: Box<dyn Iterator<Item = usize>> = if rank > 0 {
+                Box::new(0..scanned)
+            } else {
+                Box::new((list.len() - scanned..list.len()).rev())
+            };
+            let wanted = match count {
+                Some(0) => usize::MAX,
+                Some(count) => count,
+                None => 1,
+            };
+            indexes
+                .filter(|&i| list[i].as_slice() == args[2])
+                .skip(rank.unsigned_abs() as usize - 1)
+                .take(wanted)
+                .collect()
+        }
+    };
+    Ok(match count {
+        Some(_) => RespValue::Array(
+            positions
+                .into_iter()
+                .map(|pos| RespValue::Integer(pos as i64))
+                .collect(),
+        ),
+        None => positions
+            .first()
+            .map_or(RespValue::Null, |&pos| RespValue::Integer(pos as i64)),
+    })
+}
+
+fn lmove(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let from_head = parse_end(args[3])?;
+    let to_head = parse_end(args[4])?;
+    let moved = executor.db().lmove(args[1], args[2], from_head, to_head)?;
+    Ok(bulk_or_null(moved))
+}
+
+/// `LMOVE source destination RIGHT LEFT`; with one key it rotates the list.
+fn rpoplpush(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let moved = executor.db().lmove(args[1], args[2], false, true)?;
+    Ok(bulk_or_null(moved))
+}
+
+fn lmpop(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let numkeys = parse_int::<i64>(args[1])
+        .ok()
+        .filter(|&numkeys| numkeys > 0)
+        .ok_or_else(|| {
+            CommandError::Custom(String::from("ERR numkeys should be greater than 0"))
+        })?;
+    let end_at = usize::try_from(numkeys)
+        .ok()
+        .and_then(|numkeys| numkeys.checked_add(2))
+        .filter(|&end_at| end_at < args.len())
+        .ok_or(CommandError::Syntax)?;
+    let from_head = parse_end(args[end_at])?;
+    let count = match &args[end_at + 1..] {
+        [] => 1,
+        [option, count] if option.eq_ignore_ascii_case(b"COUNT") => parse_int::<i64>(count)
+            .ok()
+            .filter(|&count| count > 0)
+            .ok_or_else(|| {
+                CommandError::Custom(String::from("ERR count should be greater than 0"))
+            })? as usize,
+        _ => return Err(CommandError::Syntax),
+    };
+    let mut db = executor.db();
+    for key in &args[2..end_at] {
+        if let Some(popped) = db.pop(key, from_head, count)? {
+            return Ok(RespValue::Array(vec![
+                RespValue::BulkString(key.to_vec()),
+                RespValue::Array(popped.into_iter().map(RespValue::BulkString).collect()),
+            ]));
+        }
+    }
+    Ok(RespValue::NullArray)
+}
+
+/// Parses a blocking timeout in (possibly fractional) seconds; `None`
+/// means wait forever.
+fn parse_timeout(arg: &[u8]) -> Result<Option<Duration>, CommandError> {
+    let seconds = std::str::from_utf8(arg)
+        .ok()
+        .and_then(|text| text.parse::<f64>().ok())
+        .filter(|seconds| seconds.is_finite())
+        .ok_or_else(|| {
+            CommandError::Custom(String::from("ERR timeout is not a float or out of range"))
+        })?;
+    if seconds < 0.0 {
+        return Err(CommandError::Custom(String::from(
+            "ERR timeout is negative",
+        )));
+    }
+    if seconds == 0.0 {
+        return Ok(None);
+    }
+    Duration::try_from_secs_f64(seconds)
+        .map(Some)
+        .map_err(|_| CommandError::Custom(String::from("ERR timeout is out of range")))
+}
+
+/// `LEFT` or `RIGHT`, as taken by `LMOVE`, `LMPOP` and friends; `true`
+/// for the head.
+fn parse_end(arg: &[u8]) -> Result<bool, CommandError> {
+    match arg.to_ascii_uppercase().as_slice() {
+        b"LEFT" => Ok(true),
+        b"RIGHT" => Ok(false),
+        _ => Err(CommandError::Syntax),
+    }
+}
+
+fn key_and_element(served: Option<Served>) -> RespValue {
+    match served {
+        Some((key, element)) => RespValue::Array(vec![
+            RespValue::BulkString(key),
+            RespValue::BulkString(element),
+        ]),
+        None => RespValue::NullArray,
+    }
+}
+
+/// Shared body of `BLPOP` and `BRPOP`: pops from the first non-empty list,
+/// or blocks on all of them.
+fn blocking_pop(executor: &mut CommandExecutor, args: &[&[u8]], from_head: bool) -> CommandResult {
+    let keys = &args[1..args.len() - 1];
+    let timeout = parse_timeout(args[args.len() - 1])?;
+    let mut db = executor.db();
+    for key in keys {
+        if let Some(mut popped) = db.pop(key, from_head, 1)? {
+            let element = popped.pop();
+            return Ok(key_and_element(
+                element.map(|element| (key.to_vec(), element)),
+            ));
+        }
+    }
+    let wait = ListWait {
+        keys: keys.iter().map(|key| key.to_vec()).collect(),
+        from_head,
+        target: None,
+    };
+    let receiver = db.block(executor.session.id, wait);
+    drop(db);
+    executor.block(receiver, timeout, key_and_element)
+}
+
+fn blpop(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    blocking_pop(executor, args, true)
+}
+
+fn brpop(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    blocking_pop(executor, args, false)
+}
+
+fn blmove(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let from_head = parse_end(args[3])?;
+    let to_head = parse_end(args[4])?;
+    let timeout = parse_timeout(args[5])?;
+    let mut db = executor.db();
+    if let Some(element) = db.lmove(args[1], args[2], from_head, to_head)? {
+        return Ok(RespValue::BulkString(element));
+    }
+    let wait = ListWait {
+        keys: vec![args[1].to_vec()],
+        from_head,
+        target: Some((args[2].to_vec(), to_head)),
+    };
+    let receiver = db.block(executor.session.id, wait);
+    drop(db);
+    executor.block(receiver, timeout, |served| {
+        bulk_or_null(served.map(|(_, element)| element))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internal::clock::{ManualClock, SystemClock};
+    use crate::internal::cmd::{ok, tests::command, CommandExecutor};
+    use crate::internal::db::{Store, DEFAULT_DATABASES};
+    use crate::internal::resp::RespValue;
+
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn lrange_clamps_out_of_range_indexes() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        command(&["RPUSH", "lst", "a", "b", "c"]).accept(&mut client);
+
+        assert_eq!(
+            command(&["LRANGE", "lst", "5", "10"]).accept(&mut client),
+            RespValue::Array(vec![])
+        );
+        assert_eq!(
+            command(&["LRANGE", "lst", "-100", "1"]).accept(&mut client),
+            RespValue::Array(vec![
+                RespValue::BulkString(b"a".to_vec()),
+                RespValue::BulkString(b"b".to_vec()),
+            ])
+        );
+        command(&["LPOP", "lst"]).accept(&mut client);
+        command(&["LPOP", "lst"]).accept(&mut client);
+        command(&["LPOP", "lst"]).accept(&mut client);
+        assert_eq!(
+            command(&["LRANGE", "lst", "0", "-1"]).accept(&mut client),
+            RespValue::Array(vec![])
+        );
+    }
+
+    #[test]
+    fn list_commands() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let bulk = |s: &str| RespValue::BulkString(s.as_bytes().to_vec());
+        let bulks = |items: &[&str]| RespValue::Array(items.iter().map(|s| bulk(s)).collect());
+        let ints = |items: &[i64]| {
+            RespValue::Array(items.iter().map(|&i| RespValue::Integer(i)).collect())
+        };
+
+        assert_eq!(
+            command(&["LPUSHX", "lst", "a"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["EXISTS", "lst"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        command(&["RPUSH", "lst", "a", "b", "c", "d", "e"]).accept(&mut client);
+        assert_eq!(
+            command(&["RPUSHX", "lst", "f"]).accept(&mut client),
+            RespValue::Integer(6)
+        );
+        assert_eq!(command(&["RPOP", "lst"]).accept(&mut client), bulk("f"));
+        assert_eq!(
+            command(&["LPOP", "lst", "2"]).accept(&mut client),
+            bulks(&["a", "b"])
+        );
+        assert_eq!(
+            command(&["RPOP", "lst", "2"]).accept(&mut client),
+            bulks(&["e", "d"])
+        );
+        assert_eq!(
+            command(&["LPOP", "lst", "0"]).accept(&mut client),
+            bulks(&[])
+        );
+        assert_eq!(
+            command(&["LPOP", "lst", "-1"]).accept(&mut client),
+            RespValue::Error(String::from("ERR value is out of range, must be positive"))
+        );
+        assert_eq!(
+            command(&["LPOP", "lst", "5"]).accept(&mut client),
+            bulks(&["c"])
+        );
+        assert_eq!(
+            command(&["EXISTS", "lst"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["LPOP", "lst", "1"]).accept(&mut client),
+            RespValue::NullArray
+        );
+        assert_eq!(
+            command(&["RPOP", "lst"]).accept(&mut client),
+            RespValue::Null
+        );
+
+        command(&["RPUSH", "lst", "a", "b", "c"]).accept(&mut client);
+        assert_eq!(
+            command(&["LINDEX", "lst", "-1"]).accept(&mut client),
+            bulk("c")
+        );
+        assert_eq!(
+            command(&["LINDEX", "lst", "3"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["LSET", "lst", "1", "B"]).accept(&mut client),
+            ok()
+        );
+        assert_eq!(
+            command(&["LSET", "lst", "3", "x"]).accept(&mut client),
+            RespValue::Error(String::from("ERR index out of range"))
+        );
+        assert_eq!(
+            command(&["LSET", "nosuch", "0", "x"]).accept(&mut client),
+            RespValue::Error(String::from("ERR no such key"))
+        );
+        assert_eq!(
+            command(&["LINSERT", "lst", "BEFORE", "B", "x"]).accept(&mut client),
+            RespValue::Integer(4)
+        );
+        assert_eq!(
+            command(&["LINSERT", "lst", "after", "c", "y"]).accept(&mut client),
+            RespValue::Integer(5)
+        );
+        assert_eq!(
+            command(&["LINSERT", "lst", "AFTER", "zz", "y"]).accept(&mut client),
+            RespValue::Integer(-1)
+        );
+        assert_eq!(
+            command(&["LINSERT", "nosuch", "AFTER", "a", "y"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["LINSERT", "lst", "MIDDLE", "a", "y"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+        assert_eq!(
+            command(&["LRANGE", "lst", "0", "-1"]).accept(&mut client),
+            bulks(&["a", "x", "B", "c", "y"])
+        );
+
+        command(&["DEL", "lst"]).accept(&mut client);
+        command(&["RPUSH", "lst", "x", "a", "x", "b", "x", "c", "x"]).accept(&mut client);
+        assert_eq!(
+            command(&["LREM", "lst", "-2", "x"]).accept(&mut client),
+            RespValue::Integer(2)
+        );
+        assert_eq!(
+            command(&["LRANGE", "lst", "0", "-1"]).accept(&mut client),
+            bulks(&["x", "a", "x", "b", "c"])
+        );
+        assert_eq!(
+            command(&["LREM", "lst", "1", "x"]).accept(&mut client),
+            RespValue::Integer(1)
+        );
+        assert_eq!(
+            command(&["LREM", "lst", "0", "x"]).accept(&mut client),
+            RespValue::Integer(1)
+        );
+        assert_eq!(
+            command(&["LTRIM", "lst", "1", "-1"]).accept(&mut client),
+            ok()
+        );
+        assert_eq!(
+            command(&["LRANGE", "lst", "0", "-1"]).accept(&mut client),
+            bulks(&["b", "c"])
+        );
+        assert_eq!(
+            command(&["LTRIM", "lst", "5", "10"]).accept(&mut client),
+            ok()
+        );
+        assert_eq!(
+            command(&["EXISTS", "lst"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        command(&["RPUSH", "lst", "a"]).accept(&mut client);
+        command(&["LREM", "lst", "0", "a"]).accept(&mut client);
+        assert_eq!(
+            command(&["EXISTS", "lst"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+
+        command(&["RPUSH", "lst", "a", "b", "c", "1", "2", "3", "c", "c"]).accept(&mut client);
+        assert_eq!(
+            command(&["LPOS", "lst", "c"]).accept(&mut client),
+            RespValue::Integer(2)
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "c", "RANK", "2"]).accept(&mut client),
+            RespValue::Integer(6)
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "c", "RANK", "-1"]).accept(&mut client),
+            RespValue::Integer(7)
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "c", "COUNT", "0"]).accept(&mut client),
+            ints(&[2, 6, 7])
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "c", "RANK", "-1", "COUNT", "2"]).accept(&mut client),
+            ints(&[7, 6])
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "c", "COUNT", "0", "MAXLEN", "4"]).accept(&mut client),
+            ints(&[2])
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "x"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["LPOS", "nosuch", "x", "COUNT", "1"]).accept(&mut client),
+            ints(&[])
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "c", "RANK", "0"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the last match"
+            ))
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "c", "COUNT", "-1"]).accept(&mut client),
+            RespValue::Error(String::from("ERR COUNT can't be negative"))
+        );
+        assert_eq!(
+            command(&["LPOS", "lst", "c", "MAXLEN", "-1"]).accept(&mut client),
+            RespValue::Error(String::from("ERR MAXLEN can't be negative"))
+        );
+
+        command(&["SET", "str", "v"]).accept(&mut client);
+        assert_eq!(
+            command(&["RPUSHX", "str", "v"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "WRONGTYPE Operation against a key holding the wrong kind of value"
+            ))
+        );
+    }
+
+    #[test]
+    fn list_moves() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let bulk = |s: &str| RespValue::BulkString(s.as_bytes().to_vec());
+        let bulks = |items: &[&str]| RespValue::Array(items.iter().map(|s| bulk(s)).collect());
+
+        command(&["RPUSH", "src", "a", "b", "c"]).accept(&mut client);
+        assert_eq!(
+            command(&["LMOVE", "src", "dst", "LEFT", "RIGHT"]).accept(&mut client),
+            bulk("a")
+        );
+        assert_eq!(
+            command(&["LMOVE", "src", "dst", "right", "left"]).accept(&mut client),
+            bulk("c")
+        );
+        assert_eq!(
+            command(&["LRANGE", "dst", "0", "-1"]).accept(&mut client),
+            bulks(&["c", "a"])
+        );
+        assert_eq!(
+            command(&["RPOPLPUSH", "src", "dst"]).accept(&mut client),
+            bulk("b")
+        );
+        assert_eq!(
+            command(&["EXISTS", "src"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["RPOPLPUSH", "src", "dst"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["LMOVE", "dst", "dst", "LEFT", "NOWHERE"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+
+        // With a single key RPOPLPUSH rotates the list in place, TTL and all.
+        assert_eq!(
+            command(&["RPOPLPUSH", "dst", "dst"]).accept(&mut client),
+            bulk("a")
+        );
+        assert_eq!(
+            command(&["LRANGE", "dst", "0", "-1"]).accept(&mut client),
+            bulks(&["a", "b", "c"])
+        );
+        command(&["RPUSH", "one", "x"]).accept(&mut client);
+        command(&["EXPIRE", "one", "100"]).accept(&mut client);
+        assert_eq!(
+            command(&["RPOPLPUSH", "one", "one"]).accept(&mut client),
+            bulk("x")
+        );
+        assert_eq!(
+            command(&["TTL", "one"]).accept(&mut client),
+            RespValue::Integer(100)
+        );
+
+        command(&["SET", "str", "v"]).accept(&mut client);
+        let wrongtype = RespValue::Error(String::from(
+            "WRONGTYPE Operation against a key holding the wrong kind of value",
+        ));
+        assert_eq!(
+            command(&["LMOVE", "dst", "str", "LEFT", "LEFT"]).accept(&mut client),
+            wrongtype
+        );
+        assert_eq!(
+            command(&["LLEN", "dst"]).accept(&mut client),
+            RespValue::Integer(3)
+        );
+        // With nothing to move, the destination's type is never checked.
+        assert_eq!(
+            command(&["LMOVE", "nosuch", "str", "LEFT", "LEFT"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["RPOPLPUSH", "nosuch", "str"]).accept(&mut client),
+            RespValue::Null
+        );
+
+        assert_eq!(
+            command(&["LMPOP", "2", "nosuch", "dst", "RIGHT", "COUNT", "2"]).accept(&mut client),
+            RespValue::Array(vec![bulk("dst"), bulks(&["c", "b"])])
+        );
+        assert_eq!(
+            command(&["LMPOP", "1", "dst", "LEFT", "COUNT", "10"]).accept(&mut client),
+            RespValue::Array(vec![bulk("dst"), bulks(&["a"])])
+        );
+        assert_eq!(
+            command(&["LMPOP", "2", "dst", "nosuch", "LEFT"]).accept(&mut client),
+            RespValue::NullArray
+        );
+        assert_eq!(
+            command(&["LMPOP", "2", "str", "dst", "LEFT"]).accept(&mut client),
+            wrongtype
+        );
+        assert_eq!(
+            command(&["LMPOP", "0", "dst", "LEFT"]).accept(&mut client),
+            RespValue::Error(String::from("ERR numkeys should be greater than 0"))
+        );
+        assert_eq!(
+            command(&["LMPOP", "3", "a", "b", "LEFT"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+        assert_eq!(
+            command(&["LMPOP", "1", "a", "LEFT", "COUNT", "0"]).accept(&mut client),
+            RespValue::Error(String::from("ERR count should be greater than 0"))
+        );
+        assert_eq!(
+            command(&["LMPOP", "1", "a", "LEFT", "LIMIT", "1"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+
+        assert_eq!(
+            command(&["COMMAND", "GETKEYS", "LMPOP", "2", "a", "b", "LEFT", "COUNT", "1"])
+                .accept(&mut client),
+            bulks(&["a", "b"])
+        );
+        let info = command(&["COMMAND", "INFO", "lmpop"]).accept(&mut client);
+        let RespValue::Array(entries) = info else {
+            panic!("expected array, got {:?}", info);
+        };
+        let RespValue::Array(ref lmpop) = entries[0] else {
+            panic!("expected array, got {:?}", entries[0]);
+        };
+        let RespValue::Set(ref flags) = lmpop[2] else {
+            panic!("expected set, got {:?}", lmpop[2]);
+        };
+        assert!(flags.contains(&RespValue::SimpleString(String::from("movablekeys"))));
+        assert_eq!(lmpop[3], RespValue::Integer(0));
+    }
+
+    #[test]
+    fn list_keys_expire_and_pushes_keep_ttl() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let clock = Arc::new(ManualClock::new());
+        let mut client = CommandExecutor::new(store, clock.clone());
+        let bulk = |s: &str| RespValue::BulkString(s.as_bytes().to_vec());
+
+        command(&["RPUSH", "kept", "a"]).accept(&mut client);
+        command(&["EXPIRE", "kept", "100"]).accept(&mut client);
+        assert_eq!(
+            command(&["RPUSH", "kept", "b"]).accept(&mut client),
+            RespValue::Integer(2)
+        );
+        assert_eq!(
+            command(&["LPUSH", "kept", "c"]).accept(&mut client),
+            RespValue::Integer(3)
+        );
+        assert_eq!(
+            command(&["LPUSHX", "kept", "d"]).accept(&mut client),
+            RespValue::Integer(4)
+        );
+        assert_eq!(
+            command(&["TTL", "kept"]).accept(&mut client),
+            RespValue::Integer(100)
+        );
+
+        command(&["RPUSH", "gone", "a", "b"]).accept(&mut client);
+        command(&["PEXPIRE", "gone", "100"]).accept(&mut client);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(
+            command(&["LLEN", "gone"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["LRANGE", "gone", "0", "-1"]).accept(&mut client),
+            RespValue::Array(vec![])
+        );
+        assert_eq!(
+            command(&["LPOP", "gone"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["LINDEX", "gone", "0"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["RPUSHX", "gone", "x"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+
+        // Pushing onto an expired key starts a fresh list without a TTL.
+        command(&["RPUSH", "gone", "c"]).accept(&mut client);
+        command(&["PEXPIRE", "gone", "100"]).accept(&mut client);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(
+            command(&["RPUSH", "gone", "new"]).accept(&mut client),
+            RespValue::Integer(1)
+        );
+        assert_eq!(
+            command(&["LRANGE", "gone", "0", "-1"]).accept(&mut client),
+            RespValue::Array(vec![bulk("new")])
+        );
+        assert_eq!(
+            command(&["TTL", "gone"]).accept(&mut client),
+            RespValue::Integer(-1)
+        );
+    }
+
+    #[tokio::test]
+    async fn blocking_list_pops() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut pusher = CommandExecutor::new(Arc::clone(&store), Arc::new(SystemClock));
+        let mut first = CommandExecutor::new(Arc::clone(&store), Arc::new(SystemClock));
+        let mut second = CommandExecutor::new(Arc::clone(&store), Arc::new(SystemClock));
+        let bulk = |s: &str| RespValue::BulkString(s.as_bytes().to_vec());
+        let pair = |key: &str, element: &str| RespValue::Array(vec![bulk(key), bulk(element)]);
+
+        command(&["RPUSH", "q", "a"]).accept(&mut pusher);
+        assert_eq!(
+            command(&["BLPOP", "empty", "q", "0"]).accept(&mut first),
+            pair("q", "a")
+        );
+        assert!(first.take_blocked().is_none());
+
+        // Waiters are served in the order they blocked, one element each.
+        command(&["BLPOP", "q", "0"]).accept(&mut first);
+        let mut first_wait = first.take_blocked().expect("BLPOP should block");
+        command(&["BRPOP", "other", "q", "0"]).accept(&mut second);
+        let mut second_wait = second.take_blocked().expect("BRPOP should block");
+        assert_eq!(
+            command(&["RPUSH", "q", "x", "y"]).accept(&mut pusher),
+            RespValue::Integer(2)
+        );
+        assert_eq!(first_wait.wait().await, pair("q", "x"));
+        assert_eq!(second_wait.wait().await, pair("q", "y"));
+        assert_eq!(
+            command(&["EXISTS", "q"]).accept(&mut pusher),
+            RespValue::Integer(0)
+        );
+
+        // Pushes from another connection wake a waiting client.
+        command(&["BLPOP", "q", "5"]).accept(&mut first);
+        let mut first_wait = first.take_blocked().expect("BLPOP should block");
+        let other = Arc::clone(&store);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let mut client = CommandExecutor::new(other, Arc::new(SystemClock));
+            command(&["LPUSH", "q", "late"]).accept(&mut client);
+        });
+        assert_eq!(first_wait.wait().await, pair("q", "late"));
+
+        command(&["BLPOP", "q", "0.05"]).accept(&mut first);
+        let mut first_wait = first.take_blocked().expect("BLPOP should block");
+        assert_eq!(first_wait.wait().await, RespValue::NullArray);
+
+        // A client that goes away is never handed an element.
+        command(&["BLPOP", "q", "0"]).accept(&mut first);
+        drop(first.take_blocked());
+        command(&["RPUSH", "q", "z"]).accept(&mut pusher);
+        assert_eq!(
+            command(&["LLEN", "q"]).accept(&mut pusher),
+            RespValue::Integer(1)
+        );
+
+        command(&["BLMOVE", "src", "dst", "RIGHT", "LEFT", "0"]).accept(&mut first);
+        let mut first_wait = first.take_blocked().expect("BLMOVE should block");
+        command(&["BLPOP", "dst", "0"]).accept(&mut second);
+        let mut second_wait = second.take_blocked().expect("BLPOP should block");
+        command(&["RPUSH", "src", "a", "b"]).accept(&mut pusher);
+        assert_eq!(first_wait.wait().await, bulk("b"));
+        assert_eq!(second_wait.wait().await, pair("dst", "b"));
+        assert_eq!(
+            command(&["LRANGE", "src", "0", "-1"]).accept(&mut pusher),
+            RespValue::Array(vec![bulk("a")])
+        );
+        assert_eq!(
+            command(&["BLMOVE", "src", "dst", "LEFT", "LEFT", "0"]).accept(&mut first),
+            bulk("a")
+        );
+        command(&["BLMOVE", "src", "dst", "LEFT", "LEFT", "0.01"]).accept(&mut first);
+        let mut first_wait = first.take_blocked().expect("BLMOVE should block");
+        assert_eq!(first_wait.wait().await, RespValue::Null);
+
+        assert_eq!(
+            command(&["BLPOP", "q", "-1"]).accept(&mut first),
+            RespValue::Error(String::from("ERR timeout is negative"))
+        );
+        assert_eq!(
+            command(&["BLPOP", "q", "soon"]).accept(&mut first),
+            RespValue::Error(String::from("ERR timeout is not a float or out of range"))
+        );
+        assert_eq!(
+            command(&["BLMOVE", "src", "dst", "UP", "LEFT", "0"]).accept(&mut first),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+        command(&["SET", "str", "v"]).accept(&mut pusher);
+        assert_eq!(
+            command(&["BLPOP", "str", "0"]).accept(&mut first),
+            RespValue::Error(String::from(
+                "WRONGTYPE Operation against a key holding the wrong kind of value"
+            ))
+        );
+        assert!(first.take_blocked().is_none());
+        command(&["BLMOVE", "nosuch", "str", "LEFT", "LEFT", "0.01"]).accept(&mut first);
+        let mut first_wait = first.take_blocked().expect("BLMOVE should block");
+        assert_eq!(first_wait.wait().await, RespValue::Null);
+    }
+}
@@ -0,0 +1,264 @@
+mod bitmaps;
+mod connection;
+mod hashes;
+mod keys;
+mod lists;
+mod server;
+mod strings;
+mod table;
+
+use crate::internal::blocking::{Blocked, Served};
+use crate::internal::clock::Clock;
+use crate::internal::db::{Db, Store};
+use crate::internal::error::CommandError;
+use crate::internal::resp::RespValue;
+use crate::internal::session::Session;
+use crate::internal::traits::RespVisitor;
+
+use std::sync::{Arc, MutexGuard};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+pub type CommandResult = Result<RespValue, CommandError>;
+
+pub struct CommandExecutor {
+    store: Arc<Store>,
+    clock: Arc<dyn Clock>,
+    session: Session,
+    blocked: Option<Blocked>,
+}
+
+impl CommandExecutor {
+    pub fn new(store: Arc<Store>, clock: Arc<dyn Clock>) -> Self {
+        let session = Session::new(store.next_client_id());
+        CommandExecutor {
+            store,
+            clock,
+            session,
+            blocked: None,
+        }
+    }
+
+    pub fn protocol(&self) -> u8 {
+        self.session.protocol
+    }
+
+    /// The command just executed parked instead of replying; the caller
+    /// must wait on it before handling anything else from this client.
+    pub fn take_blocked(&mut self) -> Option<Blocked> {
+        self.blocked.take()
+    }
+
+    /// Parks the current command; the returned placeholder is never sent.
+    fn block(
+        &mut self,
+        receiver: oneshot::Receiver<Result<Served, CommandError>>,
+        timeout: Option<Duration>,
+        reply: fn(Option<Served>) -> RespValue,
+    ) -> CommandResult {
+        self.blocked = Some(Blocked::new(
+            Arc::clone(&self.store),
+            self.session.db,
+            self.session.id,
+            receiver,
+            timeout,
+            reply,
+        ));
+        Ok(RespValue::Null)
+    }
+
+    fn db(&self) -> MutexGuard<'_, Db> {
+        self.db_at(self.session.db)
+    }
+
+    fn db_at(&self, index: usize) -> MutexGuard<'_, Db> {
+        let mut db = self.store.db(index);
+        db.set_time(self.clock.now());
+        db
+    }
+
+    fn execute(&mut self, array: &[RespValue]) -> CommandResult {
+        let args = bulk_args(array)?;
+        let name = match args.first() {
+            Some(name) => *name,
+            None => return Err(CommandError::Protocol(String::from("empty command"))),
+        };
+        let spec = table::lookup(name).ok_or_else(|| CommandError::UnknownCommand {
+            name: String::from_utf8_lossy(name).into_owned(),
+            args: args[1..]
+                .iter()
+                .map(|a| format!("'{}' ", String::from_utf8_lossy(a)))
+                .collect(),
+        })?;
+        spec.check_arity(&args)?;
+        (spec.handler)(self, &args)
+    }
+}
+
+impl RespVisitor for CommandExecutor {
+    fn visit_array(&mut self, array: &[RespValue]) -> RespValue {
+        match self.execute(array) {
+            Ok(reply) => reply,
+            Err(err) => err.into(),
+        }
+    }
+}
+
+fn bulk_args(array: &[RespValue]) -> Result<Vec<&[u8]>, CommandError> {
+    array
+        .iter()
+        .map(|arg| match arg {
+            RespValue::BulkString(b) => Ok(b.as_slice()),
+            _ => Err(CommandError::Protocol(String::from(
+                "expected bulk string arguments",
+            ))),
+        })
+        .collect()
+}
+
+fn parse_int<T: std::str::FromStr>(arg: &[u8]) -> Result<T, CommandError> {
+    std::str::from_utf8(arg)
+        .ok()
+        .and_then(|s| s.parse::<T>().ok())
+        .ok_or(CommandError::NotInteger)
+}
+
+fn ok() -> RespValue {
+    RespValue::SimpleString(String::from("OK"))
+}
+
+fn bulk_or_null(value: Option<Vec<u8>>) -> RespValue {
+    match value {
+        Some(value) => RespValue::BulkString(value),
+        None => RespValue::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::clock::SystemClock;
+    use crate::internal::db::DEFAULT_DATABASES;
+
+    /// The array a client sends for the command `parts`.
+    pub(super) fn command(parts: &[&str]) -> RespValue {
+        RespValue::Array(
+            parts
+                .iter()
+                .map(|p| RespValue::BulkString(p.as_bytes().to_vec()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn keyspace_is_shared_between_clients() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut first = CommandExecutor::new(Arc::clone(&store), Arc::new(SystemClock));
+        let mut second = CommandExecutor::new(Arc::clone(&store), Arc::new(SystemClock));
+
+        command(&["SET", "foo", "bar"]).accept(&mut first);
+        assert_eq!(
+            command(&["GET", "foo"]).accept(&mut second),
+            RespValue::BulkString(b"bar".to_vec())
+        );
+    }
+
+    #[test]
+    fn values_are_binary_safe() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let blob = vec![0x00, 0xff, 0xfe, b'\r', b'\n', 0x80];
+
+        let set = RespValue::Array(vec![
+            RespValue::BulkString(b"SET".to_vec()),
+            RespValue::BulkString(blob.clone()),
+            RespValue::BulkString(blob.clone()),
+        ]);
+        set.accept(&mut client);
+        let get = RespValue::Array(vec![
+            RespValue::BulkString(b"GET".to_vec()),
+            RespValue::BulkString(blob.clone()),
+        ]);
+        let reply = get.accept(&mut client);
+        assert_eq!(reply, RespValue::BulkString(blob.clone()));
+
+        let mut encoded = vec![];
+        reply.encode(2, &mut encoded);
+        assert_eq!(&encoded[..4], b"$6\r\n");
+        assert_eq!(&encoded[4..10], &blob[..]);
+    }
+
+    #[test]
+    fn malformed_commands_reply_with_errors() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+
+        assert_eq!(
+            command(&["SET", "k", "v", "EX", "abc"]).accept(&mut client),
+            RespValue::Error(String::from("ERR value is not an integer or out of range"))
+        );
+        assert_eq!(
+            command(&["SET", "k", "v", "EX", "0"]).accept(&mut client),
+            RespValue::Error(String::from("ERR invalid expire time in 'set' command"))
+        );
+        assert_eq!(
+            command(&["ECHO"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR wrong number of arguments for 'echo' command"
+            ))
+        );
+        assert_eq!(
+            command(&["GET"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR wrong number of arguments for 'get' command"
+            ))
+        );
+        assert_eq!(
+            command(&["FOO", "bar"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR unknown command 'FOO', with args beginning with: 'bar' "
+            ))
+        );
+    }
+
+    #[test]
+    fn wrong_type_operations_reply_wrongtype() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let wrongtype = RespValue::Error(String::from(
+            "WRONGTYPE Operation against a key holding the wrong kind of value",
+        ));
+
+        command(&["SET", "str", "v"]).accept(&mut client);
+        command(&["RPUSH", "lst", "a"]).accept(&mut client);
+        assert_eq!(
+            command(&["RPUSH", "str", "a"]).accept(&mut client),
+            wrongtype
+        );
+        assert_eq!(
+            command(&["LPUSH", "str", "a"]).accept(&mut client),
+            wrongtype
+        );
+        assert_eq!(
+            command(&["LRANGE", "str", "0", "-1"]).accept(&mut client),
+            wrongtype
+        );
+        assert_eq!(command(&["GET", "lst"]).accept(&mut client), wrongtype);
+    }
+
+    #[test]
+    fn command_lookup_is_case_insensitive() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        assert_eq!(
+            command(&["pInG"]).accept(&mut client),
+            RespValue::SimpleString(String::from("PONG"))
+        );
+        assert_eq!(
+            command(&["lrange", "k", "0"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR wrong number of arguments for 'lrange' command"
+            ))
+        );
+    }
+}
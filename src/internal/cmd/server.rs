@@ -0,0 +1,327 @@
+use super::connection::REDIS_VERSION;
+use super::table::{self, CommandSpec, Flag, KeySpec};
+use super::{ok, CommandExecutor, CommandResult};
+use crate::internal::error::CommandError;
+use crate::internal::resp::RespValue;
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "command",
+        arity: -1,
+        flags: &[Flag::Loading, Flag::Stale],
+        keys: KeySpec::NONE,
+        group: "server",
+        summary: "Returns detailed information about all commands.",
+        handler: command,
+    },
+    CommandSpec {
+        name: "info",
+        arity: -1,
+        flags: &[Flag::Loading, Flag::Stale],
+        keys: KeySpec::NONE,
+        group: "server",
+        summary: "Returns information and statistics about the server.",
+        handler: info,
+    },
+    CommandSpec {
+        name: "dbsize",
+        arity: 1,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::NONE,
+        group: "server",
+        summary: "Returns the number of keys in the database.",
+        handler: dbsize,
+    },
+    CommandSpec {
+        name: "flushdb",
+        arity: -1,
+        flags: &[Flag::Write],
+        keys: KeySpec::NONE,
+        group: "server",
+        summary: "Remove all keys from the current database.",
+        handler: flushdb,
+    },
+    CommandSpec {
+        name: "flushall",
+        arity: -1,
+        flags: &[Flag::Write],
+        keys: KeySpec::NONE,
+        group: "server",
+        summary: "Removes all keys from all databases.",
+        handler: flushall,
+    },
+];
+
+/// Sections `INFO` knows about, in the order they are printed.
+const INFO_SECTIONS: &[&str] = &["server", "stats", "keyspace"];
+
+fn bulk(text: &str) -> RespValue {
+    RespValue::BulkString(text.as_bytes().to_vec())
+}
+
+fn status(text: &str) -> RespValue {
+    RespValue::SimpleString(String::from(text))
+}
+
+/// One `COMMAND INFO` entry, in the ten-field Redis 7 layout.
+fn command_info(spec: &CommandSpec) -> RespValue {
+    RespValue::Array(vec![
+        bulk(spec.name),
+        RespValue::Integer(spec.arity),
+        RespValue::Set(
+            spec.flags
+                .iter()
+                .map(|flag| flag.name())
+                .chain(spec.keys.numkeys_at.map(|_| "movablekeys"))
+                .map(status)
+                .collect(),
+        ),
+        RespValue::Integer(spec.keys.first),
+        RespValue::Integer(spec.keys.last),
+        RespValue::Integer(spec.keys.step),
+        RespValue::Set(spec.acl_categories().into_iter().map(status).collect()),
+        RespValue::Array(vec![]),
+        RespValue::Array(vec![]),
+        RespValue::Array(vec![]),
+    ])
+}
+
+fn command_docs(spec: &CommandSpec) -> RespValue {
+    RespValue::Map(vec![
+        (bulk("summary"), bulk(spec.summary)),
+        (bulk("group"), bulk(spec.group)),
+    ])
+}
+
+fn command_getkeys(args: &[&[u8]]) -> CommandResult {
+    let spec = table::lookup(args[0])
+        .ok_or_else(|| CommandError::Custom(String::from("ERR Invalid command specified")))?;
+    if !spec.arity_matches(args.len()) {
+        return Err(CommandError::Custom(String::from(
+            "ERR Invalid number of arguments specified for command",
+        )));
+    }
+    let positions = spec.keys.key_positions(args);
+    if positions.is_empty() {
+        return Err(CommandError::Custom(String::from(
+            "ERR The command has no key arguments",
+        )));
+    }
+    Ok(RespValue::Array(
+        positions
+            .into_iter()
+            .map(|pos| RespValue::BulkString(args[pos].to_vec()))
+            .collect(),
+    ))
+}
+
+fn command(_executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let Some(subcommand) = args.get(1) else {
+        return Ok(RespValue::Array(
+            table::commands().map(command_info).collect(),
+        ));
+    };
+    let subcommand = String::from_utf8_lossy(subcommand).to_uppercase();
+    let wrong_arity = || CommandError::WrongArity(format!("command|{}", subcommand.to_lowercase()));
+    match subcommand.as_str() {
+        "COUNT" if args.len() == 2 => Ok(RespValue::Integer(table::commands().count() as i64)),
+        "LIST" if args.len() == 2 => Ok(RespValue::Array(
+            table::commands().map(|spec| bulk(spec.name)).collect(),
+        )),
+        "INFO" if args.len() == 2 => Ok(RespValue::Array(
+            table::commands().map(command_info).collect(),
+        )),
+        "INFO" => Ok(RespValue::Array(
+            args[2..]
+                .iter()
+                .map(|name| match table::lookup(name) {
+                    Some(spec) => command_info(spec),
+                    None => RespValue::NullArray,
+                })
+                .collect(),
+        )),
+        "DOCS" => {
+            let specs: Vec<&CommandSpec> = if args.len() == 2 {
+                table::commands().collect()
+            } else {
+                args[2..]
+                    .iter()
+                    .filter_map(|name| table::lookup(name))
+                    .collect()
+            };
+            Ok(RespValue::Map(
+                specs
+                    .into_iter()
+                    .map(|spec| (bulk(spec.name), command_docs(spec)))
+                    .collect(),
+            ))
+        }
+        "GETKEYS" if args.len() >= 3 => command_getkeys(&args[2..]),
+        "COUNT" | "LIST" | "GETKEYS" => Err(wrong_arity()),
+        _ => Err(CommandError::Custom(format!(
+            "ERR unknown subcommand '{}'. Try COMMAND HELP.",
+            String::from_utf8_lossy(args[1])
+        ))),
+    }
+}
+
+impl CommandExecutor {
+    fn info_section(&self, section: &str) -> String {
+        let mut lines = vec![];
+        match section {
+            "server" => {
+                lines.push(format!("redis_version:{}", REDIS_VERSION));
+                lines.push(String::from("redis_mode:standalone"));
+                lines.push(format!("process_id:{}", std::process::id()));
+            }
+            "stats" => {
+                let expired_keys: u64 = (0..self.store.num_databases())
+                    .map(|index| self.store.db(index).expired_keys())
+                    .sum();
+                let stats = self.store.expire_stats();
+                lines.push(format!("expired_keys:{}", expired_keys));
+                lines.push(format!(
+                    "expired_time_cap_reached_count:{}",
+                    stats.time_cap_reached_count()
+                ));
+                lines.push(format!(
+                    "expire_cycle_cpu_milliseconds:{}",
+                    stats.cycle_cpu_milliseconds()
+                ));
+            }
+            "keyspace" => {
+                for index in 0..self.store.num_databases() {
+                    let db = self.store.db(index);
+                    if db.len() > 0 {
+                        lines.push(format!(
+                            "db{}:keys={},expires={},avg_ttl=0",
+                            index,
+                            db.len(),
+                            db.volatile_len()
+                        ));
+                    }
+                }
+            }
+            _ => return String::new(),
+        }
+        let mut title = section.to_string();
+        title[..1].make_ascii_uppercase();
+        let mut text = format!("# {}\r\n", title);
+        for line in lines {
+            text.push_str(&line);
+            text.push_str("\r\n");
+        }
+        text
+    }
+}
+
+fn info(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let requested: Vec<String> = args[1..]
+        .iter()
+        .map(|arg| String::from_utf8_lossy(arg).to_lowercase())
+        .collect();
+    let everything = requested.is_empty()
+        || requested
+            .iter()
+            .any(|section| matches!(section.as_str(), "all" | "default" | "everything"));
+    let sections: Vec<String> = INFO_SECTIONS
+        .iter()
+        .filter(|section| everything || requested.iter().any(|name| name == *section))
+        .map(|section| executor.info_section(section))
+        .collect();
+    Ok(RespValue::Verbatim {
+        format: String::from("txt"),
+        data: sections.join("\r\n").into_bytes(),
+    })
+}
+
+fn dbsize(executor: &mut CommandExecutor, _args: &[&[u8]]) -> CommandResult {
+    Ok(RespValue::Integer(executor.db().len() as i64))
+}
+
+/// Accepts the `ASYNC | SYNC` flag of `FLUSHDB` and `FLUSHALL`; both are
+/// done synchronously here.
+fn check_flush_mode(args: &[&[u8]]) -> Result<(), CommandError> {
+    match args {
+        [_] => Ok(()),
+        [_, mode] if mode.eq_ignore_ascii_case(b"ASYNC") || mode.eq_ignore_ascii_case(b"SYNC") => {
+            Ok(())
+        }
+        _ => Err(CommandError::Syntax),
+    }
+}
+
+fn flushdb(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    check_flush_mode(args)?;
+    executor.db().clear();
+    Ok(ok())
+}
+
+fn flushall(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    check_flush_mode(args)?;
+    for index in 0..executor.store.num_databases() {
+        executor.store.db(index).clear();
+    }
+    Ok(ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internal::clock::SystemClock;
+    use crate::internal::cmd::{table, tests::command, CommandExecutor};
+    use crate::internal::db::{Store, DEFAULT_DATABASES};
+    use crate::internal::resp::RespValue;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn command_introspection() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+
+        assert_eq!(
+            command(&["COMMAND", "COUNT"]).accept(&mut client),
+            RespValue::Integer(table::commands().count() as i64)
+        );
+        let info = command(&["COMMAND", "INFO", "get", "nosuch"]).accept(&mut client);
+        let RespValue::Array(entries) = info else {
+            panic!("expected array, got {:?}", info);
+        };
+        let RespValue::Array(ref get) = entries[0] else {
+            panic!("expected array, got {:?}", entries[0]);
+        };
+        assert_eq!(get[0], RespValue::BulkString(b"get".to_vec()));
+        assert_eq!(get[1], RespValue::Integer(2));
+        assert_eq!(
+            get[2],
+            RespValue::Set(vec![
+                RespValue::SimpleString(String::from("readonly")),
+                RespValue::SimpleString(String::from("fast")),
+            ])
+        );
+        assert_eq!(
+            &get[3..6],
+            &[
+                RespValue::Integer(1),
+                RespValue::Integer(1),
+                RespValue::Integer(1)
+            ]
+        );
+        assert_eq!(entries[1], RespValue::NullArray);
+
+        assert_eq!(
+            command(&["COMMAND", "GETKEYS", "SET", "foo", "bar"]).accept(&mut client),
+            RespValue::Array(vec![RespValue::BulkString(b"foo".to_vec())])
+        );
+        assert_eq!(
+            command(&["COMMAND", "GETKEYS", "PING"]).accept(&mut client),
+            RespValue::Error(String::from("ERR The command has no key arguments"))
+        );
+        assert_eq!(
+            command(&["COMMAND", "GETKEYS", "GET"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR Invalid number of arguments specified for command"
+            ))
+        );
+    }
+}
@@ -0,0 +1,1302 @@
+use super::table::{CommandSpec, Flag, KeySpec};
+use super::{bulk_or_null, ok, parse_int, CommandExecutor, CommandResult};
+use crate::internal::db::{Db, Expiration};
+use crate::internal::error::CommandError;
+use crate::internal::resp::RespValue;
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "set",
+        arity: -3,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist.",
+        handler: set,
+    },
+    CommandSpec {
+        name: "get",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Returns the string value of a key.",
+        handler: get,
+    },
+    CommandSpec {
+        name: "setnx",
+        arity: 3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Set the string value of a key only when the key doesn't exist.",
+        handler: setnx,
+    },
+    CommandSpec {
+        name: "setex",
+        arity: 4,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Sets the string value and expiration time of a key. Creates the key if it doesn't exist.",
+        handler: setex,
+    },
+    CommandSpec {
+        name: "psetex",
+        arity: 4,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Sets both string value and expiration time in milliseconds of a key. The key is created if it doesn't exist.",
+        handler: psetex,
+    },
+    CommandSpec {
+        name: "getset",
+        arity: 3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Returns the previous string value of a key after setting it to a new value.",
+        handler: getset,
+    },
+    CommandSpec {
+        name: "getdel",
+        arity: 2,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Returns the string value of a key after deleting the key.",
+        handler: getdel,
+    },
+    CommandSpec {
+        name: "getex",
+        arity: -2,
+        flags: &[Flag::Write, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Returns the string value of a key after setting its expiration time.",
+        handler: getex,
+    },
+    CommandSpec {
+        name: "append",
+        arity: 3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Appends a string to the value of a key. Creates the key if it doesn't exist.",
+        handler: append,
+    },
+    CommandSpec {
+        name: "strlen",
+        arity: 2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Returns the length of a string value.",
+        handler: strlen,
+    },
+    CommandSpec {
+        name: "getrange",
+        arity: 4,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Returns a substring of the string stored at a key.",
+        handler: getrange,
+    },
+    CommandSpec {
+        name: "substr",
+        arity: 4,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Returns a substring from a string value.",
+        handler: getrange,
+    },
+    CommandSpec {
+        name: "setrange",
+        arity: 4,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist.",
+        handler: setrange,
+    },
+    CommandSpec {
+        name: "lcs",
+        arity: -3,
+        flags: &[Flag::ReadOnly],
+        keys: KeySpec::new(1, 2, 1),
+        group: "string",
+        summary: "Finds the longest common substring.",
+        handler: lcs,
+    },
+    CommandSpec {
+        name: "incr",
+        arity: 2,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        handler: incr,
+    },
+    CommandSpec {
+        name: "decr",
+        arity: 2,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Decrements the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        handler: decr,
+    },
+    CommandSpec {
+        name: "incrby",
+        arity: 3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        handler: incrby,
+    },
+    CommandSpec {
+        name: "decrby",
+        arity: 3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Decrements a number from the integer value of a key. Uses 0 as initial value if the key doesn't exist.",
+        handler: decrby,
+    },
+    CommandSpec {
+        name: "incrbyfloat",
+        arity: 3,
+        flags: &[Flag::Write, Flag::DenyOom, Flag::Fast],
+        keys: KeySpec::FIRST,
+        group: "string",
+        summary: "Increment the floating point value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        handler: incrbyfloat,
+    },
+    CommandSpec {
+        name: "mset",
+        arity: -3,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::new(1, -1, 2),
+        group: "string",
+        summary: "Atomically creates or modifies the string values of one or more keys.",
+        handler: mset,
+    },
+    CommandSpec {
+        name: "msetnx",
+        arity: -3,
+        flags: &[Flag::Write, Flag::DenyOom],
+        keys: KeySpec::new(1, -1, 2),
+        group: "string",
+        summary: "Atomically modifies the string values of one or more keys only when all keys don't exist.",
+        handler: msetnx,
+    },
+    CommandSpec {
+        name: "mget",
+        arity: -2,
+        flags: &[Flag::ReadOnly, Flag::Fast],
+        keys: KeySpec::new(1, -1, 1),
+        group: "string",
+        summary: "Atomically returns the string values of one or more keys.",
+        handler: mget,
+    },
+];
+
+/// Largest string `APPEND` and `SETRANGE` may build, matching Redis'
+/// default `proto-max-bulk-len`.
+pub const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+/// The unit of an `EX | PX | EXAT | PXAT` option.
+#[derive(Clone, Copy, PartialEq)]
+enum ExpiryUnit {
+    Seconds,
+    Milliseconds,
+    UnixSeconds,
+    UnixMilliseconds,
+}
+
+/// Options of `SET` and `GETEX`, which share Redis' parser and its rules
+/// about which options may be combined.
+#[derive(Default)]
+struct StringOptions {
+    nx: bool,
+    xx: bool,
+    get: bool,
+    keep_ttl: bool,
+    persist: bool,
+    expiry: Option<(ExpiryUnit, i64)>,
+}
+
+impl StringOptions {
+    /// `for_set` selects the `SET` options (`NX`, `XX`, `GET`, `KEEPTTL`)
+    /// over the `GETEX` one (`PERSIST`).
+    fn parse(args: &[&[u8]], for_set: bool) -> Result<Self, CommandError> {
+        let mut options = StringOptions::default();
+        let mut idx = 0;
+        while idx < args.len() {
+            let unit = match args[idx].to_ascii_uppercase().as_slice() {
+                b"NX" if for_set && !options.xx => {
+                    options.nx = true;
+                    None
+                }
+                b"XX" if for_set && !options.nx => {
+                    options.xx = true;
+                    None
+                }
+                b"GET" if for_set => {
+                    options.get = true;
+                    None
+                }
+                b"KEEPTTL" if for_set && options.expiry.is_none() => {
+                    options.keep_ttl = true;
+                    None
+                }
+                b"PERSIST" if !for_set && options.expiry.is_none() => {
+                    options.persist = true;
+                    None
+                }
+                b"EX" => Some(ExpiryUnit::Seconds),
+                b"PX" => Some(ExpiryUnit::Milliseconds),
+                b"EXAT" => Some(ExpiryUnit::UnixSeconds),
+                b"PXAT" => Some(ExpiryUnit::UnixMilliseconds),
+                _ => return Err(CommandError::Syntax),
+            };
+            if let Some(unit) = unit {
+                let conflicting = options.keep_ttl
+                    || options.persist
+                    || options.expiry.is_some_and(|(other, _)| other != unit);
+                let value = args.get(idx + 1).filter(|_| !conflicting);
+                let value = value.ok_or(CommandError::Syntax)?;
+                options.expiry = Some((unit, parse_int::<i64>(value)?));
+                idx += 1;
+            }
+            idx += 1;
+        }
+        Ok(options)
+    }
+
+    fn expiration(
+        &self,
+        executor: &CommandExecutor,
+        name: &str,
+    ) -> Result<Option<Expiration>, CommandError> {
+        self.expiry
+            .map(|(unit, amount)| expiration(executor, name, unit, amount))
+            .transpose()
+    }
+}
+
+/// Turns `amount` in `unit` into an expiration, rejecting values that are
+/// not positive or whose deadline does not fit in 64-bit milliseconds.
+fn expiration(
+    executor: &CommandExecutor,
+    name: &str,
+    unit: ExpiryUnit,
+    amount: i64,
+) -> Result<Expiration, CommandError> {
+    let invalid = || CommandError::InvalidExpireTime(String::from(name));
+    if amount <= 0 {
+        return Err(invalid());
+    }
+    let unit_ms = match unit {
+        ExpiryUnit::Seconds | ExpiryUnit::UnixSeconds => 1000,
+        ExpiryUnit::Milliseconds | ExpiryUnit::UnixMilliseconds => 1,
+    };
+    let millis = amount.checked_mul(unit_ms).ok_or_else(invalid)?;
+    let relative = matches!(unit, ExpiryUnit::Seconds | ExpiryUnit::Milliseconds);
+    if relative && executor.clock.unix_millis().checked_add(millis).is_none() {
+        return Err(invalid());
+    }
+    Ok(match unit {
+        ExpiryUnit::Seconds => Expiration::Seconds(amount as u64),
+        ExpiryUnit::Milliseconds => Expiration::Milliseconds(amount as u64),
+        _ => Expiration::At(executor.clock.instant_at_unix_millis(millis)),
+    })
+}
+
+fn set(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let options = StringOptions::parse(&args[3..], true)?;
+    let expiration = options.expiration(executor, "set")?;
+    let now = executor.clock.now();
+    let mut db = executor.db();
+    // With GET the old value must be a string, checked before writing.
+    let old = if options.get { db.get(args[1])? } else { None };
+    let exists = old.is_some() || db.contains_key(args[1]);
+    if (options.nx && exists) || (options.xx && !exists) {
+        return Ok(if options.get {
+            bulk_or_null(old)
+        } else {
+            RespValue::Null
+        });
+    }
+    match expiration {
+        Some(expiration) if expiration.deadline(now) <= now => {
+            db.remove(args[1]);
+        }
+        _ if options.keep_ttl => db.set_keep_ttl(args[1].to_vec(), args[2].to_vec()),
+        _ => db.set(args[1].to_vec(), args[2].to_vec(), expiration),
+    }
+    Ok(if options.get { bulk_or_null(old) } else { ok() })
+}
+
+fn setnx(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    if db.contains_key(args[1]) {
+        return Ok(RespValue::Integer(0));
+    }
+    db.set(args[1].to_vec(), args[2].to_vec(), None);
+    Ok(RespValue::Integer(1))
+}
+
+fn setex_generic(
+    executor: &mut CommandExecutor,
+    args: &[&[u8]],
+    name: &str,
+    unit: ExpiryUnit,
+) -> CommandResult {
+    let amount = parse_int::<i64>(args[2])?;
+    let expiration = expiration(executor, name, unit, amount)?;
+    executor
+        .db()
+        .set(args[1].to_vec(), args[3].to_vec(), Some(expiration));
+    Ok(ok())
+}
+
+fn setex(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    setex_generic(executor, args, "setex", ExpiryUnit::Seconds)
+}
+
+fn psetex(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    setex_generic(executor, args, "psetex", ExpiryUnit::Milliseconds)
+}
+
+fn get(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    Ok(bulk_or_null(executor.db().get(args[1])?))
+}
+
+fn getset(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let old = db.get(args[1])?;
+    db.set(args[1].to_vec(), args[2].to_vec(), None);
+    Ok(bulk_or_null(old))
+}
+
+fn getdel(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    let value = db.get(args[1])?;
+    if value.is_some() {
+        db.remove(args[1]);
+    }
+    Ok(bulk_or_null(value))
+}
+
+fn getex(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let options = StringOptions::parse(&args[2..], false)?;
+    let expiration = options.expiration(executor, "getex")?;
+    let now = executor.clock.now();
+    let mut db = executor.db();
+    let value = db.get(args[1])?;
+    if value.is_some() {
+        match expiration {
+            Some(expiration) if expiration.deadline(now) <= now => {
+                db.remove(args[1]);
+            }
+            Some(expiration) => {
+                db.set_expiry_time(args[1], Some(expiration.deadline(now)));
+            }
+            None if options.persist => {
+                db.set_expiry_time(args[1], None);
+            }
+            None => {}
+        }
+    }
+    Ok(bulk_or_null(value))
+}
+
+fn check_string_length(len: usize) -> Result<(), CommandError> {
+    if len > MAX_STRING_LEN {
+        return Err(CommandError::Custom(String::from(
+            "ERR string exceeds maximum allowed size (proto-max-bulk-len)",
+        )));
+    }
+    Ok(())
+}
+
+fn append(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    if let Some(value) = db.string_mut(args[1])? {
+        check_string_length(value.len() + args[2].len())?;
+        value.extend_from_slice(args[2]);
+        return Ok(RespValue::Integer(value.len() as i64));
+    }
+    db.set(args[1].to_vec(), args[2].to_vec(), None);
+    Ok(RespValue::Integer(args[2].len() as i64))
+}
+
+fn strlen(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let len = executor.db().string_mut(args[1])?.map_or(0, |v| v.len());
+    Ok(RespValue::Integer(len as i64))
+}
+
+fn getrange(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let start = parse_int::<i64>(args[2])?;
+    let end = parse_int::<i64>(args[3])?;
+    let mut db = executor.db();
+    let value = match db.string_mut(args[1])? {
+        Some(value) => value,
+        None => return Ok(RespValue::BulkString(vec![])),
+    };
+    let len = value.len() as i64;
+    if (start < 0 && end < 0 && start > end) || len == 0 {
+        return Ok(RespValue::BulkString(vec![]));
+    }
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let end = if end < 0 {
+        (len + end).max(0)
+    } else {
+        end.min(len - 1)
+    };
+    if start > end {
+        return Ok(RespValue::BulkString(vec![]));
+    }
+    Ok(RespValue::BulkString(
+        value[start as usize..=end as usize].to_vec(),
+    ))
+}
+
+fn setrange(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let offset = parse_int::<i64>(args[2])?;
+    if offset < 0 {
+        return Err(CommandError::Custom(String::from(
+            "ERR offset is out of range",
+        )));
+    }
+    let offset = offset as usize;
+    let patch = args[3];
+    let mut db = executor.db();
+    let Some(value) = db.string_mut(args[1])? else {
+        if patch.is_empty() {
+            return Ok(RespValue::Integer(0));
+        }
+        check_string_length(offset + patch.len())?;
+        let mut value = vec![0; offset];
+        value.extend_from_slice(patch);
+        let len = value.len();
+        db.set(args[1].to_vec(), value, None);
+        return Ok(RespValue::Integer(len as i64));
+    };
+    if !patch.is_empty() {
+        check_string_length(offset + patch.len())?;
+        if value.len() < offset + patch.len() {
+            value.resize(offset + patch.len(), 0);
+        }
+        value[offset..offset + patch.len()].copy_from_slice(patch);
+    }
+    Ok(RespValue::Integer(value.len() as i64))
+}
+
+/// One matching stretch of an `LCS IDX` reply: inclusive ranges in the
+/// first and second string.
+struct LcsMatch {
+    a: (usize, usize),
+    b: (usize, usize),
+}
+
+/// Longest common subsequence of `a` and `b`, plus the contiguous matching
+/// ranges that make it up, reported from the end of the strings backwards
+/// as Redis does.
+fn longest_common_subsequence(a: &[u8], b: &[u8]) -> (Vec<u8>, Vec<LcsMatch>) {
+    let width = b.len() + 1;
+    let mut dp = vec![0u32; (a.len() + 1) * width];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            dp[i * width + j] = if a[i - 1] == b[j - 1] {
+                dp[(i - 1) * width + j - 1] + 1
+            } else {
+                dp[(i - 1) * width + j].max(dp[i * width + j - 1])
+            };
+        }
+    }
+
+    let mut result = vec![0u8; dp[a.len() * width + b.len()] as usize];
+    let mut idx = result.len();
+    let mut matches = vec![];
+    let mut current: Option<LcsMatch> = None;
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 && j > 0 {
+        let mut emit = false;
+        if a[i - 1] == b[j - 1] {
+            result[idx - 1] = a[i - 1];
+            match current {
+                None => {
+                    current = Some(LcsMatch {
+                        a: (i - 1, i - 1),
+                        b: (j - 1, j - 1),
+                    })
+                }
+                Some(ref mut range) if range.a.0 == i && range.b.0 == j => {
+                    range.a.0 -= 1;
+                    range.b.0 -= 1;
+                }
+                Some(_) => emit = true,
+            }
+            // A range touching the start of either string cannot grow.
+            if current
+                .as_ref()
+                .is_some_and(|range| range.a.0 == 0 || range.b.0 == 0)
+            {
+                emit = true;
+            }
+            idx -= 1;
+            i -= 1;
+            j -= 1;
+        } else {
+            if dp[(i - 1) * width + j] > dp[i * width + j - 1] {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+            emit = current.is_some();
+        }
+        if emit {
+            matches.extend(current.take());
+        }
+    }
+    (result, matches)
+}
+
+fn lcs(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let (mut len_only, mut with_idx, mut with_match_len) = (false, false, false);
+    let mut min_match_len = 0;
+    let mut idx = 3;
+    while idx < args.len() {
+        match args[idx].to_ascii_uppercase().as_slice() {
+            b"LEN" => len_only = true,
+            b"IDX" => with_idx = true,
+            b"WITHMATCHLEN" => with_match_len = true,
+            b"MINMATCHLEN" if idx + 1 < args.len() => {
+                min_match_len = parse_int::<i64>(args[idx + 1])?.max(0) as usize;
+                idx += 1;
+            }
+            _ => return Err(CommandError::Syntax),
+        }
+        idx += 1;
+    }
+    if len_only && with_idx {
+        return Err(CommandError::Custom(String::from(
+            "ERR If you want both the length and indexes, please just use IDX.",
+        )));
+    }
+
+    let (a, b) = {
+        let mut db = executor.db();
+        let wrong_type = |_| {
+            CommandError::Custom(String::from(
+                "ERR The specified keys must contain string values",
+            ))
+        };
+        let a = db.get(args[1]).map_err(wrong_type)?.unwrap_or_default();
+        let b = db.get(args[2]).map_err(wrong_type)?.unwrap_or_default();
+        (a, b)
+    };
+    if (a.len() + 1).saturating_mul(b.len() + 1).saturating_mul(4) > MAX_STRING_LEN {
+        return Err(CommandError::Custom(String::from(
+            "ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len",
+        )));
+    }
+
+    let (common, matches) = longest_common_subsequence(&a, &b);
+    if len_only {
+        return Ok(RespValue::Integer(common.len() as i64));
+    }
+    if !with_idx {
+        return Ok(RespValue::BulkString(common));
+    }
+    let range = |(start, end): (usize, usize)| {
+        RespValue::Array(vec![
+            RespValue::Integer(start as i64),
+            RespValue::Integer(end as i64),
+        ])
+    };
+    let matches = matches
+        .into_iter()
+        .filter(|m| m.a.1 - m.a.0 + 1 >= min_match_len)
+        .map(|m| {
+            let mut entry = vec![range(m.a), range(m.b)];
+            if with_match_len {
+                entry.push(RespValue::Integer((m.a.1 - m.a.0 + 1) as i64));
+            }
+            RespValue::Array(entry)
+        })
+        .collect();
+    let field = |name: &str| RespValue::BulkString(name.as_bytes().to_vec());
+    Ok(RespValue::Map(vec![
+        (field("matches"), RespValue::Array(matches)),
+        (field("len"), RespValue::Integer(common.len() as i64)),
+    ]))
+}
+
+/// Parses a stored value the way Redis' `string2ll` does: an optional
+/// minus sign and digits, without padding, a plus sign or leading zeros.
+pub fn stored_int(value: &[u8]) -> Option<i64> {
+    let digits = value.strip_prefix(b"-").unwrap_or(value);
+    let canonical = match digits {
+        [b'0'] => value.len() == 1,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    };
+    if !canonical {
+        return None;
+    }
+    std::str::from_utf8(value).ok()?.parse().ok()
+}
+
+/// Parses a float the way `INCRBYFLOAT` accepts one: no surrounding
+/// spaces, and never NaN or infinite.
+pub fn stored_float(value: &[u8]) -> Option<f64> {
+    let text = std::str::from_utf8(value).ok()?;
+    if text.is_empty() || text.trim() != text {
+        return None;
+    }
+    text.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// Formats an `INCRBYFLOAT` result like Redis: fixed point, at most 17
+/// decimal places, trailing zeros trimmed.
+///
+/// Redis adds in long double, whose extra precision keeps the rounding
+/// error of inputs like `0.1` out of the printed digits, so `0.1 + 0.2`
+/// shows as `0.3`. A sum of decimals never has more places than its
+/// operands, so rounding the f64 result to that many places drops the same
+/// error; `places` is the most any operand had.
+pub fn format_float(value: f64, places: usize) -> String {
+    let text = format!("{:.*}", places.min(17), value);
+    match text.contains('.') {
+        true => text.trim_end_matches('0').trim_end_matches('.').to_string(),
+        false => text,
+    }
+}
+
+/// How many decimal places the number written as `text` has, e.g. 2 for
+/// `1.25` and 4 for `125e-4`.
+pub fn decimal_places(text: &[u8]) -> usize {
+    let text = String::from_utf8_lossy(text);
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().unwrap_or(0)),
+        None => (&*text, 0),
+    };
+    let fraction = mantissa
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    (fraction as i64).saturating_sub(exponent).max(0) as usize
+}
+
+/// Replaces the string at `key` with `value`, keeping its TTL, or creates
+/// it without one.
+fn store_number(db: &mut Db, key: &[u8], value: Vec<u8>) -> Result<(), CommandError> {
+    match db.string_mut(key)? {
+        Some(current) => *current = value,
+        None => db.set(key.to_vec(), value, None),
+    }
+    Ok(())
+}
+
+fn incr_by(executor: &mut CommandExecutor, key: &[u8], delta: i64) -> CommandResult {
+    let mut db = executor.db();
+    let current = match db.string_mut(key)? {
+        Some(value) => stored_int(value).ok_or(CommandError::NotInteger)?,
+        None => 0,
+    };
+    let updated = current.checked_add(delta).ok_or_else(|| {
+        CommandError::Custom(String::from("ERR increment or decrement would overflow"))
+    })?;
+    store_number(&mut db, key, updated.to_string().into_bytes())?;
+    Ok(RespValue::Integer(updated))
+}
+
+fn incr(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    incr_by(executor, args[1], 1)
+}
+
+fn decr(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    incr_by(executor, args[1], -1)
+}
+
+fn incrby(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let delta = parse_int::<i64>(args[2])?;
+    incr_by(executor, args[1], delta)
+}
+
+fn decrby(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let delta = parse_int::<i64>(args[2])?;
+    let delta = delta
+        .checked_neg()
+        .ok_or_else(|| CommandError::Custom(String::from("ERR decrement would overflow")))?;
+    incr_by(executor, args[1], delta)
+}
+
+fn incrbyfloat(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let not_float = || CommandError::Custom(String::from("ERR value is not a valid float"));
+    let delta = stored_float(args[2]).ok_or_else(not_float)?;
+    let mut db = executor.db();
+    let (current, places) = match db.string_mut(args[1])? {
+        Some(value) => (
+            stored_float(value).ok_or_else(not_float)?,
+            decimal_places(value),
+        ),
+        None => (0.0, 0),
+    };
+    let updated = current + delta;
+    if !updated.is_finite() {
+        return Err(CommandError::Custom(String::from(
+            "ERR increment would produce NaN or Infinity",
+        )));
+    }
+    let text = format_float(updated, places.max(decimal_places(args[2]))).into_bytes();
+    store_number(&mut db, args[1], text.clone())?;
+    Ok(RespValue::BulkString(text))
+}
+
+/// Checks that `MSET` and `MSETNX` got whole `key value` pairs.
+fn check_pairs(args: &[&[u8]], name: &str) -> Result<(), CommandError> {
+    if args.len().is_multiple_of(2) {
+        return Err(CommandError::WrongArity(String::from(name)));
+    }
+    Ok(())
+}
+
+fn mset(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    check_pairs(args, "mset")?;
+    let mut db = executor.db();
+    for pair in args[1..].chunks(2) {
+        db.set(pair[0].to_vec(), pair[1].to_vec(), None);
+    }
+    Ok(ok())
+}
+
+fn msetnx(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    check_pairs(args, "msetnx")?;
+    let mut db = executor.db();
+    if args[1..].iter().step_by(2).any(|key| db.contains_key(key)) {
+        return Ok(RespValue::Integer(0));
+    }
+    for pair in args[1..].chunks(2) {
+        db.set(pair[0].to_vec(), pair[1].to_vec(), None);
+    }
+    Ok(RespValue::Integer(1))
+}
+
+fn mget(executor: &mut CommandExecutor, args: &[&[u8]]) -> CommandResult {
+    let mut db = executor.db();
+    Ok(RespValue::Array(
+        args[1..]
+            .iter()
+            .map(|key| bulk_or_null(db.get(key).ok().flatten()))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internal::clock::{Clock, ManualClock, SystemClock};
+    use crate::internal::cmd::{tests::command, CommandExecutor};
+    use crate::internal::db::{Store, DEFAULT_DATABASES};
+    use crate::internal::resp::RespValue;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn set_options() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let clock = Arc::new(ManualClock::new());
+        let mut client = CommandExecutor::new(store, clock.clone());
+        let ok = RespValue::SimpleString(String::from("OK"));
+        let bulk = |v: &str| RespValue::BulkString(v.as_bytes().to_vec());
+
+        assert_eq!(
+            command(&["SET", "lock", "a", "NX", "PX", "30000"]).accept(&mut client),
+            ok
+        );
+        assert_eq!(
+            command(&["SET", "lock", "b", "PX", "30000", "NX"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["SET", "lock", "b", "nx", "get"]).accept(&mut client),
+            bulk("a")
+        );
+        assert_eq!(
+            command(&["SET", "missing", "v", "XX"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["SET", "lock", "c", "XX", "GET", "KEEPTTL"]).accept(&mut client),
+            bulk("a")
+        );
+        assert_eq!(
+            command(&["PTTL", "lock"]).accept(&mut client),
+            RespValue::Integer(30000)
+        );
+        assert_eq!(
+            command(&["SET", "lock", "d", "GET"]).accept(&mut client),
+            bulk("c")
+        );
+        assert_eq!(
+            command(&["TTL", "lock"]).accept(&mut client),
+            RespValue::Integer(-1)
+        );
+        assert_eq!(
+            command(&["SET", "fresh", "v", "GET"]).accept(&mut client),
+            RespValue::Null
+        );
+
+        let at = (clock.unix_millis() / 1000 + 100).to_string();
+        command(&["SET", "k", "v", "EXAT", &at]).accept(&mut client);
+        assert_eq!(
+            command(&["EXPIRETIME", "k"]).accept(&mut client),
+            RespValue::Integer(at.parse().unwrap())
+        );
+        let pxat = (clock.unix_millis() + 500).to_string();
+        command(&["SET", "k", "v", "PXAT", &pxat]).accept(&mut client);
+        assert_eq!(
+            command(&["PTTL", "k"]).accept(&mut client),
+            RespValue::Integer(500)
+        );
+        assert_eq!(
+            command(&["SET", "k", "v", "PXAT", "1"]).accept(&mut client),
+            ok
+        );
+        assert_eq!(command(&["GET", "k"]).accept(&mut client), RespValue::Null);
+
+        command(&["RPUSH", "list", "a"]).accept(&mut client);
+        assert_eq!(
+            command(&["SET", "list", "v", "GET"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "WRONGTYPE Operation against a key holding the wrong kind of value"
+            ))
+        );
+        assert_eq!(
+            command(&["SET", "list", "v", "NX"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(command(&["SET", "list", "v"]).accept(&mut client), ok);
+
+        let syntax = RespValue::Error(String::from("ERR syntax error"));
+        for bad in [
+            &["SET", "k", "v", "NX", "XX"][..],
+            &["SET", "k", "v", "EX", "10", "PX", "10"],
+            &["SET", "k", "v", "KEEPTTL", "EX", "10"],
+            &["SET", "k", "v", "EX", "10", "KEEPTTL"],
+            &["SET", "k", "v", "EX"],
+            &["SET", "k", "v", "PERSIST"],
+        ] {
+            assert_eq!(command(bad).accept(&mut client), syntax, "{:?}", bad);
+        }
+        assert_eq!(
+            command(&["SET", "k", "v", "EX", "0"]).accept(&mut client),
+            RespValue::Error(String::from("ERR invalid expire time in 'set' command"))
+        );
+        assert_eq!(
+            command(&["SET", "k", "v", "EX", "ten"]).accept(&mut client),
+            RespValue::Error(String::from("ERR value is not an integer or out of range"))
+        );
+    }
+
+    #[test]
+    fn legacy_set_and_get_variants() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let clock = Arc::new(ManualClock::new());
+        let mut client = CommandExecutor::new(store, clock.clone());
+        let bulk = |v: &str| RespValue::BulkString(v.as_bytes().to_vec());
+
+        assert_eq!(
+            command(&["SETNX", "k", "1"]).accept(&mut client),
+            RespValue::Integer(1)
+        );
+        assert_eq!(
+            command(&["SETNX", "k", "2"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["GETSET", "k", "3"]).accept(&mut client),
+            bulk("1")
+        );
+        assert_eq!(
+            command(&["GETSET", "new", "x"]).accept(&mut client),
+            RespValue::Null
+        );
+
+        command(&["SETEX", "s", "10", "v"]).accept(&mut client);
+        command(&["PSETEX", "p", "1500", "v"]).accept(&mut client);
+        assert_eq!(
+            command(&["TTL", "s"]).accept(&mut client),
+            RespValue::Integer(10)
+        );
+        assert_eq!(
+            command(&["PTTL", "p"]).accept(&mut client),
+            RespValue::Integer(1500)
+        );
+        assert_eq!(
+            command(&["SETEX", "s", "-1", "v"]).accept(&mut client),
+            RespValue::Error(String::from("ERR invalid expire time in 'setex' command"))
+        );
+        assert_eq!(
+            command(&["GETSET", "s", "w"]).accept(&mut client),
+            bulk("v")
+        );
+        assert_eq!(
+            command(&["TTL", "s"]).accept(&mut client),
+            RespValue::Integer(-1)
+        );
+
+        assert_eq!(
+            command(&["GETEX", "s", "EX", "100"]).accept(&mut client),
+            bulk("w")
+        );
+        assert_eq!(
+            command(&["TTL", "s"]).accept(&mut client),
+            RespValue::Integer(100)
+        );
+        assert_eq!(
+            command(&["GETEX", "s", "PERSIST"]).accept(&mut client),
+            bulk("w")
+        );
+        assert_eq!(
+            command(&["TTL", "s"]).accept(&mut client),
+            RespValue::Integer(-1)
+        );
+        assert_eq!(
+            command(&["GETEX", "s", "PXAT", "1"]).accept(&mut client),
+            bulk("w")
+        );
+        assert_eq!(command(&["GET", "s"]).accept(&mut client), RespValue::Null);
+        assert_eq!(
+            command(&["GETEX", "s", "NX"]).accept(&mut client),
+            RespValue::Error(String::from("ERR syntax error"))
+        );
+        assert_eq!(
+            command(&["GETEX", "missing", "EX", "10"]).accept(&mut client),
+            RespValue::Null
+        );
+
+        assert_eq!(command(&["GETDEL", "k"]).accept(&mut client), bulk("3"));
+        assert_eq!(
+            command(&["GETDEL", "k"]).accept(&mut client),
+            RespValue::Null
+        );
+        command(&["RPUSH", "list", "a"]).accept(&mut client);
+        assert_eq!(
+            command(&["GETDEL", "list"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "WRONGTYPE Operation against a key holding the wrong kind of value"
+            ))
+        );
+    }
+
+    #[test]
+    fn string_editing_commands() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let bulk = |v: &[u8]| RespValue::BulkString(v.to_vec());
+
+        assert_eq!(
+            command(&["APPEND", "log", "Hello"]).accept(&mut client),
+            RespValue::Integer(5)
+        );
+        command(&["EXPIRE", "log", "100"]).accept(&mut client);
+        assert_eq!(
+            command(&["APPEND", "log", " World"]).accept(&mut client),
+            RespValue::Integer(11)
+        );
+        assert_eq!(
+            command(&["TTL", "log"]).accept(&mut client),
+            RespValue::Integer(100)
+        );
+        assert_eq!(
+            command(&["STRLEN", "log"]).accept(&mut client),
+            RespValue::Integer(11)
+        );
+        assert_eq!(
+            command(&["STRLEN", "missing"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+
+        for (start, end, expected) in [
+            ("0", "3", &b"Hell"[..]),
+            ("-3", "-1", b"rld"),
+            ("0", "-1", b"Hello World"),
+            ("10", "100", b"d"),
+            ("-1", "-5", b""),
+            ("-100", "2", b"Hel"),
+            ("5", "3", b""),
+        ] {
+            assert_eq!(
+                command(&["GETRANGE", "log", start, end]).accept(&mut client),
+                bulk(expected),
+                "GETRANGE {} {}",
+                start,
+                end
+            );
+        }
+        assert_eq!(
+            command(&["SUBSTR", "missing", "0", "-1"]).accept(&mut client),
+            bulk(b"")
+        );
+
+        assert_eq!(
+            command(&["SETRANGE", "log", "6", "Redis"]).accept(&mut client),
+            RespValue::Integer(11)
+        );
+        assert_eq!(
+            command(&["GET", "log"]).accept(&mut client),
+            bulk(b"Hello Redis")
+        );
+        assert_eq!(
+            command(&["SETRANGE", "rec", "3", "ab"]).accept(&mut client),
+            RespValue::Integer(5)
+        );
+        assert_eq!(
+            command(&["GET", "rec"]).accept(&mut client),
+            bulk(b"\0\0\0ab")
+        );
+        assert_eq!(
+            command(&["SETRANGE", "empty", "10", ""]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["GET", "empty"]).accept(&mut client),
+            RespValue::Null
+        );
+        assert_eq!(
+            command(&["SETRANGE", "rec", "-1", "x"]).accept(&mut client),
+            RespValue::Error(String::from("ERR offset is out of range"))
+        );
+        assert_eq!(
+            command(&["SETRANGE", "rec", "536870911", "xy"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR string exceeds maximum allowed size (proto-max-bulk-len)"
+            ))
+        );
+    }
+
+    #[test]
+    fn lcs_options() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        command(&["SET", "key1", "ohmytext"]).accept(&mut client);
+        command(&["SET", "key2", "mynewtext"]).accept(&mut client);
+        let range = |start: i64, end: i64| {
+            RespValue::Array(vec![RespValue::Integer(start), RespValue::Integer(end)])
+        };
+        let reply = |matches: Vec<RespValue>| {
+            RespValue::Map(vec![
+                (
+                    RespValue::BulkString(b"matches".to_vec()),
+                    RespValue::Array(matches),
+                ),
+                (
+                    RespValue::BulkString(b"len".to_vec()),
+                    RespValue::Integer(6),
+                ),
+            ])
+        };
+
+        assert_eq!(
+            command(&["LCS", "key1", "key2"]).accept(&mut client),
+            RespValue::BulkString(b"mytext".to_vec())
+        );
+        assert_eq!(
+            command(&["LCS", "key1", "key2", "LEN"]).accept(&mut client),
+            RespValue::Integer(6)
+        );
+        assert_eq!(
+            command(&["LCS", "key1", "key2", "IDX"]).accept(&mut client),
+            reply(vec![
+                RespValue::Array(vec![range(4, 7), range(5, 8)]),
+                RespValue::Array(vec![range(2, 3), range(0, 1)]),
+            ])
+        );
+        assert_eq!(
+            command(&[
+                "LCS",
+                "key1",
+                "key2",
+                "IDX",
+                "MINMATCHLEN",
+                "4",
+                "WITHMATCHLEN"
+            ])
+            .accept(&mut client),
+            reply(vec![RespValue::Array(vec![
+                range(4, 7),
+                range(5, 8),
+                RespValue::Integer(4)
+            ])])
+        );
+        assert_eq!(
+            command(&["LCS", "key1", "missing"]).accept(&mut client),
+            RespValue::BulkString(vec![])
+        );
+        assert_eq!(
+            command(&["LCS", "key1", "key2", "LEN", "IDX"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR If you want both the length and indexes, please just use IDX."
+            ))
+        );
+        command(&["RPUSH", "list", "a"]).accept(&mut client);
+        assert_eq!(
+            command(&["LCS", "key1", "list"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR The specified keys must contain string values"
+            ))
+        );
+    }
+
+    #[test]
+    fn counters() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let not_integer =
+            RespValue::Error(String::from("ERR value is not an integer or out of range"));
+
+        assert_eq!(
+            command(&["INCR", "hits"]).accept(&mut client),
+            RespValue::Integer(1)
+        );
+        command(&["EXPIRE", "hits", "100"]).accept(&mut client);
+        assert_eq!(
+            command(&["INCRBY", "hits", "41"]).accept(&mut client),
+            RespValue::Integer(42)
+        );
+        assert_eq!(
+            command(&["DECRBY", "hits", "50"]).accept(&mut client),
+            RespValue::Integer(-8)
+        );
+        assert_eq!(
+            command(&["DECR", "hits"]).accept(&mut client),
+            RespValue::Integer(-9)
+        );
+        assert_eq!(
+            command(&["GET", "hits"]).accept(&mut client),
+            RespValue::BulkString(b"-9".to_vec())
+        );
+        assert_eq!(
+            command(&["TTL", "hits"]).accept(&mut client),
+            RespValue::Integer(100)
+        );
+
+        command(&["SET", "max", "9223372036854775807"]).accept(&mut client);
+        assert_eq!(
+            command(&["INCR", "max"]).accept(&mut client),
+            RespValue::Error(String::from("ERR increment or decrement would overflow"))
+        );
+        assert_eq!(
+            command(&["DECRBY", "hits", "-9223372036854775808"]).accept(&mut client),
+            RespValue::Error(String::from("ERR decrement would overflow"))
+        );
+        for bad in ["abc", "01", " 1", "+1", "1.5", ""] {
+            command(&["SET", "bad", bad]).accept(&mut client);
+            assert_eq!(
+                command(&["INCR", "bad"]).accept(&mut client),
+                not_integer,
+                "{:?}",
+                bad
+            );
+        }
+        assert_eq!(
+            command(&["INCRBY", "hits", "x"]).accept(&mut client),
+            not_integer
+        );
+
+        for (start, delta, expected) in [
+            ("10.50", "0.1", "10.6"),
+            ("5.0e3", "2.0e2", "5200"),
+            ("3", "1.5", "4.5"),
+            ("1", "-1", "0"),
+            ("0.1", "0.2", "0.3"),
+            ("-0.1", "-0.2", "-0.3"),
+            ("1.2345678901234567", "0", "1.2345678901234567"),
+            ("5e-1", "1", "1.5"),
+            ("0", "1e-20", "0"),
+            ("0", "0.00000000000000001", "0.00000000000000001"),
+        ] {
+            command(&["SET", "f", start]).accept(&mut client);
+            assert_eq!(
+                command(&["INCRBYFLOAT", "f", delta]).accept(&mut client),
+                RespValue::BulkString(expected.as_bytes().to_vec())
+            );
+        }
+        assert_eq!(
+            command(&["INCRBYFLOAT", "new", "2.5"]).accept(&mut client),
+            RespValue::BulkString(b"2.5".to_vec())
+        );
+        assert_eq!(
+            command(&["INCRBYFLOAT", "f", "inf"]).accept(&mut client),
+            RespValue::Error(String::from("ERR value is not a valid float"))
+        );
+        command(&["SET", "f", "1.7976931348623157e308"]).accept(&mut client);
+        assert_eq!(
+            command(&["INCRBYFLOAT", "f", "1.7976931348623157e308"]).accept(&mut client),
+            RespValue::Error(String::from("ERR increment would produce NaN or Infinity"))
+        );
+
+        command(&["RPUSH", "list", "a"]).accept(&mut client);
+        assert_eq!(
+            command(&["INCR", "list"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "WRONGTYPE Operation against a key holding the wrong kind of value"
+            ))
+        );
+    }
+
+    #[test]
+    fn multi_key_strings() {
+        let store = Arc::new(Store::new(DEFAULT_DATABASES));
+        let mut client = CommandExecutor::new(store, Arc::new(SystemClock));
+        let bulk = |v: &str| RespValue::BulkString(v.as_bytes().to_vec());
+
+        assert_eq!(
+            command(&["MSET", "a", "1", "b", "2"]).accept(&mut client),
+            RespValue::SimpleString(String::from("OK"))
+        );
+        command(&["RPUSH", "list", "x"]).accept(&mut client);
+        assert_eq!(
+            command(&["MGET", "a", "missing", "list", "b"]).accept(&mut client),
+            RespValue::Array(vec![bulk("1"), RespValue::Null, RespValue::Null, bulk("2")])
+        );
+        assert_eq!(
+            command(&["MSET", "a", "1", "b"]).accept(&mut client),
+            RespValue::Error(String::from(
+                "ERR wrong number of arguments for 'mset' command"
+            ))
+        );
+
+        assert_eq!(
+            command(&["MSETNX", "c", "3", "a", "changed"]).accept(&mut client),
+            RespValue::Integer(0)
+        );
+        assert_eq!(
+            command(&["MGET", "a", "c"]).accept(&mut client),
+            RespValue::Array(vec![bulk("1"), RespValue::Null])
+        );
+        assert_eq!(
+            command(&["MSETNX", "c", "3", "d", "4"]).accept(&mut client),
+            RespValue::Integer(1)
+        );
+        assert_eq!(
+            command(&["MGET", "c", "d"]).accept(&mut client),
+            RespValue::Array(vec![bulk("3"), bulk("4")])
+        );
+        assert_eq!(
+            command(&["COMMAND", "GETKEYS", "MSET", "a", "1", "b", "2"]).accept(&mut client),
+            RespValue::Array(vec![bulk("a"), bulk("b")])
+        );
+    }
+}
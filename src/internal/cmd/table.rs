@@ -0,0 +1,187 @@
+use super::{
+    bitmaps, connection, hashes, keys, lists, server, strings, CommandExecutor, CommandResult,
+};
+use crate::internal::error::CommandError;
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+pub type Handler = fn(&mut CommandExecutor, &[&[u8]]) -> CommandResult;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Flag {
+    Write,
+    ReadOnly,
+    DenyOom,
+    Admin,
+    Blocking,
+    Fast,
+    Loading,
+    Stale,
+}
+
+impl Flag {
+    pub fn name(self) -> &'static str {
+        match self {
+            Flag::Write => "write",
+            Flag::ReadOnly => "readonly",
+            Flag::DenyOom => "denyoom",
+            Flag::Admin => "admin",
+            Flag::Blocking => "blocking",
+            Flag::Fast => "fast",
+            Flag::Loading => "loading",
+            Flag::Stale => "stale",
+        }
+    }
+}
+
+/// Legacy `first key, last key, step` triple from `COMMAND INFO`.
+///
+/// Positions count from the command name at 0; a negative `last` counts
+/// back from the end (`-1` is the last argument).
+#[derive(Clone, Copy, Debug)]
+pub struct KeySpec {
+    pub first: i64,
+    pub last: i64,
+    pub step: i64,
+    /// For commands like `LMPOP` whose keys are preceded by their count:
+    /// the position of that count. The triple is then all zeros, as in
+    /// Redis, and the command reports the `movablekeys` flag.
+    pub numkeys_at: Option<usize>,
+}
+
+impl KeySpec {
+    pub const NONE: KeySpec = KeySpec::new(0, 0, 0);
+    pub const FIRST: KeySpec = KeySpec::new(1, 1, 1);
+
+    pub const fn new(first: i64, last: i64, step: i64) -> Self {
+        KeySpec {
+            first,
+            last,
+            step,
+            numkeys_at: None,
+        }
+    }
+
+    pub const fn numkeys(at: usize) -> Self {
+        KeySpec {
+            numkeys_at: Some(at),
+            ..KeySpec::NONE
+        }
+    }
+
+    /// Returns the indexes of the key arguments in `args`.
+    pub fn key_positions(&self, args: &[&[u8]]) -> Vec<usize> {
+        if let Some(at) = self.numkeys_at {
+            let numkeys = args
+                .get(at)
+                .and_then(|arg| std::str::from_utf8(arg).ok())
+                .and_then(|text| text.parse::<usize>().ok())
+                .unwrap_or(0);
+            return (at + 1..args.len()).take(numkeys).collect();
+        }
+        if self.first <= 0 {
+            return vec![];
+        }
+        let argc = args.len() as i64;
+        let last = if self.last < 0 {
+            argc + self.last
+        } else {
+            self.last
+        };
+        (self.first..=last.min(argc - 1))
+            .step_by(self.step.max(1) as usize)
+            .map(|pos| pos as usize)
+            .collect()
+    }
+}
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// Exact argument count when positive, minimum when negative; both
+    /// include the command name itself.
+    pub arity: i64,
+    pub flags: &'static [Flag],
+    pub keys: KeySpec,
+    pub group: &'static str,
+    pub summary: &'static str,
+    pub handler: Handler,
+}
+
+impl CommandSpec {
+    pub fn arity_matches(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity >= 0 {
+            argc == self.arity
+        } else {
+            argc >= -self.arity
+        }
+    }
+
+    pub fn check_arity(&self, args: &[&[u8]]) -> Result<(), CommandError> {
+        if !self.arity_matches(args.len()) {
+            return Err(CommandError::WrongArity(String::from(self.name)));
+        }
+        Ok(())
+    }
+
+    pub fn has_flag(&self, flag: Flag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// ACL categories Redis would report for this command.
+    pub fn acl_categories(&self) -> Vec<&'static str> {
+        let mut categories = vec![];
+        if self.has_flag(Flag::Write) {
+            categories.push("@write");
+        }
+        if self.has_flag(Flag::ReadOnly) {
+            categories.push("@read");
+        }
+        let group = match self.group {
+            "string" => Some("@string"),
+            "list" => Some("@list"),
+            "hash" => Some("@hash"),
+            "bitmap" => Some("@bitmap"),
+            "generic" => Some("@keyspace"),
+            "connection" => Some("@connection"),
+            _ => None,
+        };
+        categories.extend(group);
+        if self.has_flag(Flag::Admin) {
+            categories.extend(["@admin", "@dangerous"]);
+        }
+        categories.push(if self.has_flag(Flag::Fast) {
+            "@fast"
+        } else {
+            "@slow"
+        });
+        if self.has_flag(Flag::Blocking) {
+            categories.push("@blocking");
+        }
+        categories
+    }
+}
+
+/// Every command the server understands, in family order.
+pub fn commands() -> impl Iterator<Item = &'static CommandSpec> {
+    [
+        connection::COMMANDS,
+        server::COMMANDS,
+        keys::COMMANDS,
+        strings::COMMANDS,
+        bitmaps::COMMANDS,
+        lists::COMMANDS,
+        hashes::COMMANDS,
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Finds a command by name, ignoring case.
+pub fn lookup(name: &[u8]) -> Option<&'static CommandSpec> {
+    static INDEX: OnceLock<HashMap<&'static str, &'static CommandSpec>> = OnceLock::new();
+    let index = INDEX.get_or_init(|| commands().map(|spec| (spec.name, spec)).collect());
+    let name = std::str::from_utf8(name).ok()?.to_ascii_lowercase();
+    index.get(name.as_str()).copied()
+}
@@ -0,0 +1,78 @@
+use crate::internal::db::DEFAULT_DATABASES;
+use crate::internal::resp::ProtoLimits;
+
+/// Server settings, taken from `redis-server` style `--name value` flags.
+pub struct Config {
+    pub port: u16,
+    pub databases: usize,
+    pub limits: ProtoLimits,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            port: 6379,
+            databases: DEFAULT_DATABASES,
+            limits: ProtoLimits::default(),
+        }
+    }
+}
+
+/// Parses a size with an optional Redis memory unit (`1k`, `512mb`, `1gb`).
+fn parse_memory(value: &str) -> Option<usize> {
+    let lower = value.to_ascii_lowercase();
+    let split = lower
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(lower.len());
+    let (digits, unit) = lower.split_at(split);
+    let multiplier: usize = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    digits.parse::<usize>().ok()?.checked_mul(multiplier)
+}
+
+impl Config {
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<Config, String> {
+        let mut config = Config::default();
+        while let Some(flag) = args.next() {
+            let name = flag
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Unexpected argument '{}'", flag))?
+                .to_ascii_lowercase();
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for '{}'", flag))?;
+            let invalid = || format!("Invalid value '{}' for '{}'", value, flag);
+            match name.as_str() {
+                "port" => config.port = value.parse().map_err(|_| invalid())?,
+                "databases" => {
+                    config.databases = match value.parse() {
+                        Ok(n) if n > 0 => n,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "proto-max-bulk-len" => {
+                    config.limits.max_bulk_len = parse_memory(&value).ok_or_else(invalid)?
+                }
+                "max-multibulk-len" => {
+                    config.limits.max_multibulk_len = value.parse().map_err(|_| invalid())?
+                }
+                "max-nesting-depth" => {
+                    config.limits.max_depth = value.parse().map_err(|_| invalid())?
+                }
+                "client-query-buffer-limit" => {
+                    config.limits.max_query_buffer = parse_memory(&value).ok_or_else(invalid)?
+                }
+                _ => return Err(format!("Unknown option '{}'", flag)),
+            }
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,601 @@
+use crate::internal::blocking::{BlockedClients, ListWait, Served};
+use crate::internal::error::CommandError;
+use crate::internal::expire::{ActiveExpireStats, VolatileKeys};
+use crate::internal::random;
+use crate::internal::scan::ScanMap;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+pub const DEFAULT_DATABASES: usize = 16;
+
+pub enum Expiration {
+    Seconds(u64),
+    Milliseconds(u64),
+    /// An absolute deadline, e.g. from `EXAT` or `PXAT`.
+    At(Instant),
+}
+
+/// Field-value pairs of a hash key.
+pub type Hash = ScanMap<Vec<u8>>;
+
+#[derive(Clone)]
+pub enum RedisValue {
+    String(Vec<u8>),
+    List(VecDeque<Vec<u8>>),
+    Hash(Hash),
+}
+
+impl RedisValue {
+    /// The name `TYPE` reports for this value.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RedisValue::String(_) => "string",
+            RedisValue::List(_) => "list",
+            RedisValue::Hash(_) => "hash",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ValueEntry {
+    pub value: RedisValue,
+    pub expiry_time: Option<Instant>,
+}
+
+/// A single logical database (the target of `SELECT <index>`).
+pub struct Db {
+    /// Kept in scan order, which is what `SCAN` cursors walk.
+    data: ScanMap<ValueEntry>,
+    /// Every key in `data` that has an `expiry_time`.
+    volatile: VolatileKeys,
+    expired_keys: u64,
+    /// Time the current command runs at; see `set_time`.
+    now: Instant,
+    blocked: BlockedClients,
+}
+
+impl Db {
+    pub fn new() -> Self {
+        Db {
+            data: ScanMap::default(),
+            volatile: VolatileKeys::default(),
+            expired_keys: 0,
+            now: Instant::now(),
+            blocked: BlockedClients::default(),
+        }
+    }
+
+    /// Pins the time every expiry check and new deadline is measured
+    /// against until the next call, so a whole command sees one instant.
+    pub fn set_time(&mut self, now: Instant) {
+        self.now = now;
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn volatile_len(&self) -> usize {
+        self.volatile.len()
+    }
+
+    /// Keys removed because their TTL passed, lazily or by the active cycle.
+    pub fn expired_keys(&self) -> u64 {
+        self.expired_keys
+    }
+
+    /// Stores `entry` under `key`, replacing whatever was there.
+    pub fn insert(&mut self, key: Vec<u8>, entry: ValueEntry) {
+        if entry.expiry_time.is_some() {
+            self.volatile.insert(&key);
+        } else {
+            self.volatile.remove(&key);
+        }
+        let wakes = matches!(&entry.value, RedisValue::List(list) if !list.is_empty())
+            && self.blocked.is_waited_on(&key);
+        if wakes {
+            self.data.insert(key.clone(), entry);
+            self.wake_blocked(&key);
+        } else {
+            self.data.insert(key, entry);
+        }
+    }
+
+    pub fn set(&mut self, key: Vec<u8>, value: Vec<u8>, expiry_opt: Option<Expiration>) {
+        let entry = ValueEntry {
+            value: RedisValue::String(value),
+            expiry_time: expiry_opt.map(|expiration| expiration.deadline(self.now)),
+        };
+        self.insert(key, entry);
+    }
+
+    /// Like `set`, but an existing key keeps its TTL (`SET ... KEEPTTL`).
+    pub fn set_keep_ttl(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        match self.live_entry(&key) {
+            Some(entry) => entry.value = RedisValue::String(value),
+            None => self.set(key, value, None),
+        }
+    }
+
+    /// Appends to the list at `key`, creating it without a TTL if needed;
+    /// an existing list keeps its TTL.
+    pub fn rpush(&mut self, key: &[u8], values: Vec<Vec<u8>>) -> Result<i64, CommandError> {
+        let len = match self.list_entry(key).value {
+            RedisValue::List(ref mut buf) => {
+                buf.extend(values);
+                buf.len() as i64
+            }
+            _ => return Err(CommandError::WrongType),
+        };
+        self.wake_blocked(key);
+        Ok(len)
+    }
+
+    pub fn lpush(&mut self, key: &[u8], values: Vec<Vec<u8>>) -> Result<i64, CommandError> {
+        let len = match self.list_entry(key).value {
+            RedisValue::List(ref mut buf) => {
+                for value in values {
+                    buf.push_front(value);
+                }
+                buf.len() as i64
+            }
+            _ => return Err(CommandError::WrongType),
+        };
+        self.wake_blocked(key);
+        Ok(len)
+    }
+
+    /// The live entry at `key`, or a fresh empty list without a TTL.
+    fn list_entry(&mut self, key: &[u8]) -> &mut ValueEntry {
+        if self.live_entry(key).is_none() {
+            let entry = ValueEntry {
+                value: RedisValue::List(VecDeque::new()),
+                expiry_time: None,
+            };
+            self.insert(key.to_vec(), entry);
+        }
+        self.data.get_mut(key).expect("entry was just inserted")
+    }
+
+    /// Looks up `key`, first evicting it if its TTL has passed.
+    ///
+    /// Every command reads and writes keys through here, whatever the
+    /// value type, so none of them can observe an expired key.
+    fn live_entry(&mut self, key: &[u8]) -> Option<&mut ValueEntry> {
+        let expired = self
+            .data
+            .get(key)?
+            .expiry_time
+            .is_some_and(|expiry| self.now >= expiry);
+        if expired {
+            self.unlink(key);
+            self.expired_keys += 1;
+            return None;
+        }
+        self.data.get_mut(key)
+    }
+
+    pub fn get(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, CommandError> {
+        match self.live_entry(key) {
+            Some(entry) => match entry.value {
+                RedisValue::String(ref val) => Ok(Some(val.clone())),
+                _ => Err(CommandError::WrongType),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The string stored at `key`, for commands that edit it in place.
+    pub fn string_mut(&mut self, key: &[u8]) -> Result<Option<&mut Vec<u8>>, CommandError> {
+        match self.live_entry(key) {
+            Some(entry) => match entry.value {
+                RedisValue::String(ref mut val) => Ok(Some(val)),
+                _ => Err(CommandError::WrongType),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Returns `None` for a missing key, otherwise the key's deadline.
+    pub fn expiry_time(&mut self, key: &[u8]) -> Option<Option<Instant>> {
+        self.live_entry(key).map(|entry| entry.expiry_time)
+    }
+
+    /// Replaces the deadline of an existing key; `false` if it is missing.
+    pub fn set_expiry_time(&mut self, key: &[u8], expiry_time: Option<Instant>) -> bool {
+        match self.live_entry(key) {
+            Some(entry) => {
+                entry.expiry_time = expiry_time;
+            }
+            None => return false,
+        }
+        if expiry_time.is_some() {
+            self.volatile.insert(key);
+        } else {
+            self.volatile.remove(key);
+        }
+        true
+    }
+
+    fn unlink(&mut self, key: &[u8]) -> Option<ValueEntry> {
+        self.volatile.remove(key);
+        self.data.remove(key)
+    }
+
+    /// Deletes `key`; `false` if it did not exist (or had already expired).
+    pub fn remove(&mut self, key: &[u8]) -> bool {
+        self.take(key).is_some()
+    }
+
+    /// Removes `key` and hands back its value and deadline.
+    pub fn take(&mut self, key: &[u8]) -> Option<ValueEntry> {
+        self.live_entry(key)?;
+        self.unlink(key)
+    }
+
+    pub fn contains_key(&mut self, key: &[u8]) -> bool {
+        self.live_entry(key).is_some()
+    }
+
+    /// A copy of the value and deadline stored at `key`.
+    pub fn entry(&mut self, key: &[u8]) -> Option<ValueEntry> {
+        self.live_entry(key).cloned()
+    }
+
+    pub fn type_of(&mut self, key: &[u8]) -> Option<&'static str> {
+        self.live_entry(key).map(|entry| entry.value.type_name())
+    }
+
+    /// Every key whose TTL has not passed.
+    pub fn keys(&mut self) -> Vec<Vec<u8>> {
+        let keys: Vec<Vec<u8>> = self.data.keys().cloned().collect();
+        keys.into_iter()
+            .filter(|key| self.live_entry(key).is_some())
+            .collect()
+    }
+
+    /// One `SCAN` page of at most about `count` keys; see `ScanMap::page`.
+    pub fn scan(&mut self, cursor: u64, count: usize) -> (u64, Vec<Vec<u8>>) {
+        let (next, page) = self.data.page(cursor, count);
+        let page: Vec<Vec<u8>> = page.into_iter().map(<[u8]>::to_vec).collect();
+        let live = page
+            .into_iter()
+            .filter(|key| self.live_entry(key).is_some())
+            .collect();
+        (next, live)
+    }
+
+    /// A key picked at random, skipping ones whose TTL has passed.
+    pub fn random_key(&mut self) -> Option<Vec<u8>> {
+        while !self.data.is_empty() {
+            let index = random::below(self.data.len());
+            let key = self.data.keys().nth(index).cloned()?;
+            if self.live_entry(&key).is_some() {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.volatile = VolatileKeys::default();
+    }
+
+    /// One sampling round of the active expire cycle: checks up to
+    /// `samples` random keys with a TTL and deletes the ones that are past
+    /// `now`. Returns how many keys were sampled and how many expired.
+    pub fn active_expire_step(&mut self, now: Instant, samples: usize) -> (usize, usize) {
+        let sampled = samples.min(self.volatile.len());
+        let mut expired = 0;
+        for _ in 0..sampled {
+            let Some(key) = self.volatile.random().map(<[u8]>::to_vec) else {
+                break;
+            };
+            let is_expired = self
+                .data
+                .get(&key)
+                .and_then(|entry| entry.expiry_time)
+                .is_some_and(|expiry| now >= expiry);
+            if is_expired {
+                self.unlink(&key);
+                self.expired_keys += 1;
+                expired += 1;
+            }
+        }
+        (sampled, expired)
+    }
+
+    pub fn llen(&mut self, key: &[u8]) -> Result<i64, CommandError> {
+        Ok(self.list_mut(key)?.map_or(0, |list| list.len() as i64))
+    }
+
+    pub fn lrange(
+        &mut self,
+        key: &[u8],
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Vec<u8>>, CommandError> {
+        let Some(list) = self.list_mut(key)? else {
+            return Ok(vec![]);
+        };
+        let len = list.len() as i64;
+        let start_idx = if start < 0 {
+            i64::max(0, len + start)
+        } else {
+            start
+        };
+        let end_idx = if end < 0 { len + end } else { end };
+
+        if start_idx > end_idx || start_idx >= len {
+            return Ok(vec![]);
+        }
+        let end_idx = i64::min(len - 1, end_idx);
+
+        Ok(list
+            .range(start_idx as usize..=end_idx as usize)
+            .cloned()
+            .collect())
+    }
+
+    /// Pops up to `count` elements from the head (or tail) of a list,
+    /// deleting the key once it is empty. `None` if the key is missing.
+    pub fn pop(
+        &mut self,
+        key: &[u8],
+        from_head: bool,
+        count: usize,
+    ) -> Result<Option<Vec<Vec<u8>>>, CommandError> {
+        let Some(list) = self.list_mut(key)? else {
+            return Ok(None);
+        };
+        let count = count.min(list.len());
+        let popped = if from_head {
+            list.drain(..count).collect()
+        } else {
+            list.drain(list.len() - count..).rev().collect()
+        };
+        self.remove_if_empty(key);
+        Ok(Some(popped))
+    }
+
+    /// The list stored at `key`, for commands that edit it in place.
+    pub fn list_mut(&mut self, key: &[u8]) -> Result<Option<&mut VecDeque<Vec<u8>>>, CommandError> {
+        match self.live_entry(key) {
+            Some(entry) => match entry.value {
+                RedisValue::List(ref mut list) => Ok(Some(list)),
+                _ => Err(CommandError::WrongType),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The hash stored at `key`, for commands that read or edit it.
+    pub fn hash_mut(&mut self, key: &[u8]) -> Result<Option<&mut Hash>, CommandError> {
+        match self.live_entry(key) {
+            Some(entry) => match entry.value {
+                RedisValue::Hash(ref mut hash) => Ok(Some(hash)),
+                _ => Err(CommandError::WrongType),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Like `hash_mut`, but creates an empty hash without a TTL if `key`
+    /// is missing.
+    pub fn hash_or_insert(&mut self, key: &[u8]) -> Result<&mut Hash, CommandError> {
+        if self.live_entry(key).is_none() {
+            let entry = ValueEntry {
+                value: RedisValue::Hash(Hash::default()),
+                expiry_time: None,
+            };
+            self.insert(key.to_vec(), entry);
+        }
+        Ok(self.hash_mut(key)?.expect("entry was just inserted"))
+    }
+
+    /// Deletes `key` if it holds an empty list or hash; like Redis, such
+    /// keys exist only while they have elements.
+    pub fn remove_if_empty(&mut self, key: &[u8]) {
+        let empty = match self.data.get(key).map(|entry| &entry.value) {
+            Some(RedisValue::List(list)) => list.is_empty(),
+            Some(RedisValue::Hash(hash)) => hash.is_empty(),
+            _ => false,
+        };
+        if empty {
+            self.unlink(key);
+        }
+    }
+
+    /// Moves one element from one end of `source` to one end of
+    /// `destination`, which may be the same list. `None` if `source` is
+    /// missing.
+    pub fn lmove(
+        &mut self,
+        source: &[u8],
+        destination: &[u8],
+        from_head: bool,
+        to_head: bool,
+    ) -> Result<Option<Vec<u8>>, CommandError> {
+        // Like Redis, the destination's type only matters once there is
+        // an element to move onto it.
+        if self.list_mut(source)?.is_none_or(|list| list.is_empty()) {
+            return Ok(None);
+        }
+        self.list_mut(destination)?;
+        let Some(element) = self.pop_one(source, from_head)? else {
+            return Ok(None);
+        };
+        self.push_one(destination, element.clone(), to_head);
+        self.remove_if_empty(source);
+        self.wake_blocked(destination);
+        Ok(Some(element))
+    }
+
+    /// Takes one element off a list without deleting the emptied key, so
+    /// that pushing it straight back keeps the key's TTL.
+    fn pop_one(&mut self, key: &[u8], from_head: bool) -> Result<Option<Vec<u8>>, CommandError> {
+        Ok(self.list_mut(key)?.and_then(|list| {
+            if from_head {
+                list.pop_front()
+            } else {
+                list.pop_back()
+            }
+        }))
+    }
+
+    /// Pushes onto a list the caller has already checked the type of.
+    fn push_one(&mut self, key: &[u8], element: Vec<u8>, to_head: bool) {
+        if let RedisValue::List(ref mut list) = self.list_entry(key).value {
+            if to_head {
+                list.push_front(element);
+            } else {
+                list.push_back(element);
+            }
+        }
+    }
+
+    /// Registers `client` as blocked until one of `wait.keys` holds a list.
+    pub fn block(
+        &mut self,
+        client: u64,
+        wait: ListWait,
+    ) -> oneshot::Receiver<Result<Served, CommandError>> {
+        self.blocked.add(client, wait)
+    }
+
+    pub fn unblock(&mut self, client: u64) {
+        self.blocked.remove(client);
+    }
+
+    /// Hands elements of the list at `key` to the clients blocked on it,
+    /// longest-waiting first, for as long as the list has elements.
+    fn wake_blocked(&mut self, key: &[u8]) {
+        if !self.blocked.is_waited_on(key) {
+            return;
+        }
+        let mut ready = VecDeque::from([key.to_vec()]);
+        while let Some(key) = ready.pop_front() {
+            while matches!(self.list_mut(&key), Ok(Some(list)) if !list.is_empty()) {
+                let Some(waiter) = self.blocked.pop_waiter(&key) else {
+                    break;
+                };
+                let from_head = waiter.wait.from_head;
+                if let Some((destination, _)) = &waiter.wait.target {
+                    if self.list_mut(destination).is_err() {
+                        let _ = waiter.sender.send(Err(CommandError::WrongType));
+                        continue;
+                    }
+                }
+                let Ok(Some(element)) = self.pop_one(&key, from_head) else {
+                    break;
+                };
+                if let Err(Ok((_, element))) =
+                    waiter.sender.send(Ok((key.clone(), element.clone())))
+                {
+                    // The client gave up in the meantime; put it back.
+                    if let Ok(Some(list)) = self.list_mut(&key) {
+                        if from_head {
+                            list.push_front(element);
+                        } else {
+                            list.push_back(element);
+                        }
+                    }
+                    continue;
+                }
+                if let Some((destination, to_head)) = waiter.wait.target {
+                    self.push_one(&destination, element, to_head);
+                    if destination != key && self.blocked.is_waited_on(&destination) {
+                        ready.push_back(destination);
+                    }
+                }
+            }
+            self.remove_if_empty(&key);
+        }
+    }
+}
+
+impl Expiration {
+    pub fn deadline(&self, now: Instant) -> Instant {
+        match *self {
+            Expiration::Seconds(s) => now + Duration::from_secs(s),
+            Expiration::Milliseconds(s) => now + Duration::from_millis(s),
+            Expiration::At(deadline) => deadline,
+        }
+    }
+}
+
+/// Server-wide keyspace shared by every client connection.
+///
+/// Each logical database sits behind its own lock, so a command only
+/// serializes against clients that have the same database selected.
+pub struct Store {
+    databases: Vec<Mutex<Db>>,
+    next_client_id: AtomicU64,
+    expire_stats: ActiveExpireStats,
+}
+
+impl Store {
+    pub fn new(num_databases: usize) -> Self {
+        Store {
+            databases: (0..num_databases).map(|_| Mutex::new(Db::new())).collect(),
+            next_client_id: AtomicU64::new(1),
+            expire_stats: ActiveExpireStats::default(),
+        }
+    }
+
+    pub fn num_databases(&self) -> usize {
+        self.databases.len()
+    }
+
+    /// Locks database `index`, even if a command panicked while holding it.
+    ///
+    /// A panic only ends the task of the connection that hit it. Refusing
+    /// the poisoned lock would instead make every later command on the
+    /// database panic too, losing all of its keys for every client until a
+    /// restart. `Db` methods never panic between the updates that keep
+    /// `data` and its TTL set in step, so what a panicking command can
+    /// leave behind is its own partial effect, much like a Redis command
+    /// that fails half-way through a multi-key write.
+    pub fn db(&self, index: usize) -> MutexGuard<'_, Db> {
+        self.databases[index]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn next_client_id(&self) -> u64 {
+        self.next_client_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub fn expire_stats(&self) -> &ActiveExpireStats {
+        &self.expire_stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    #[test]
+    fn database_survives_a_panic_while_locked() {
+        let store = Store::new(1);
+        store.db(0).set(b"kept".to_vec(), b"1".to_vec(), None);
+        let panicked = thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let _db = store.db(0);
+                    panic!("command failed while holding the lock");
+                })
+                .join()
+                .is_err()
+        });
+        assert!(panicked);
+        assert!(store.db(0).contains_key(b"kept"));
+    }
+}
@@ -0,0 +1,32 @@
+use crate::internal::resp::RespValue;
+
+/// Failures a command can report back to the client.
+///
+/// The `Display` text is the exact error line Redis sends, including the
+/// leading error code, so clients that match on it keep working.
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum CommandError {
+    #[error("ERR unknown command '{name}', with args beginning with: {args}")]
+    UnknownCommand { name: String, args: String },
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR syntax error")]
+    Syntax,
+    #[error("ERR invalid expire time in '{0}' command")]
+    InvalidExpireTime(String),
+    #[error("ERR Protocol error: {0}")]
+    Protocol(String),
+    /// Any other reply; the message carries its own error code.
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl From<CommandError> for RespValue {
+    fn from(err: CommandError) -> Self {
+        RespValue::Error(err.to_string())
+    }
+}
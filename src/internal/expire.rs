@@ -0,0 +1,203 @@
+use crate::internal::clock::Clock;
+use crate::internal::db::Store;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::internal::random;
+
+/// How often the active expire cycle runs (Redis' default `hz 10`).
+pub const CYCLE_PERIOD: Duration = Duration::from_millis(100);
+/// Share of each period a cycle may spend expiring keys.
+const CYCLE_BUDGET: Duration = Duration::from_millis(25);
+/// Keys with a TTL sampled per database per iteration.
+const KEYS_PER_LOOP: usize = 20;
+/// Keep sampling a database while more than this percentage of the
+/// sampled keys turned out to be expired.
+const ACCEPTABLE_STALE_PERCENT: usize = 10;
+
+/// The keys of one database that carry a TTL, in a form that supports
+/// picking one at random in constant time.
+#[derive(Default)]
+pub struct VolatileKeys {
+    keys: Vec<Vec<u8>>,
+    positions: HashMap<Vec<u8>, usize>,
+}
+
+impl VolatileKeys {
+    pub fn insert(&mut self, key: &[u8]) {
+        if !self.positions.contains_key(key) {
+            self.positions.insert(key.to_vec(), self.keys.len());
+            self.keys.push(key.to_vec());
+        }
+    }
+
+    pub fn remove(&mut self, key: &[u8]) {
+        if let Some(pos) = self.positions.remove(key) {
+            self.keys.swap_remove(pos);
+            if let Some(moved) = self.keys.get(pos) {
+                if let Some(slot) = self.positions.get_mut(moved) {
+                    *slot = pos;
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn random(&self) -> Option<&[u8]> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        Some(&self.keys[random::below(self.keys.len())])
+    }
+}
+
+/// Bookkeeping for the active expire cycle, shared by the whole server.
+#[derive(Default)]
+pub struct ActiveExpireStats {
+    /// Database the next cycle starts from, so a cycle cut short by its
+    /// time budget does not starve the databases after it.
+    next_db: AtomicUsize,
+    time_cap_reached: AtomicU64,
+    cycle_micros: AtomicU64,
+}
+
+impl ActiveExpireStats {
+    pub fn time_cap_reached_count(&self) -> u64 {
+        self.time_cap_reached.load(Ordering::Relaxed)
+    }
+
+    pub fn cycle_cpu_milliseconds(&self) -> u64 {
+        self.cycle_micros.load(Ordering::Relaxed) / 1000
+    }
+}
+
+/// Runs one active expire cycle over every database and returns how many
+/// keys it expired.
+///
+/// Like Redis, each database is sampled `KEYS_PER_LOOP` keys at a time and
+/// revisited while the sample was mostly stale; the database lock is
+/// released between samples so clients are never blocked for long.
+pub fn active_expire_cycle(store: &Store, clock: &dyn Clock, budget: Duration) -> u64 {
+    let started = Instant::now();
+    let stats = store.expire_stats();
+    let num_databases = store.num_databases();
+    let first_db = stats.next_db.load(Ordering::Relaxed) % num_databases;
+    let mut expired_total = 0;
+    let mut timed_out = false;
+
+    for offset in 0..num_databases {
+        let index = (first_db + offset) % num_databases;
+        loop {
+            let (sampled, expired) = store
+                .db(index)
+                .active_expire_step(clock.now(), KEYS_PER_LOOP);
+            expired_total += expired as u64;
+            if started.elapsed() >= budget {
+                timed_out = true;
+                break;
+            }
+            if sampled == 0 || expired * 100 <= sampled * ACCEPTABLE_STALE_PERCENT {
+                break;
+            }
+        }
+        if timed_out {
+            stats.next_db.store(index, Ordering::Relaxed);
+            stats.time_cap_reached.fetch_add(1, Ordering::Relaxed);
+            break;
+        }
+    }
+
+    stats
+        .cycle_micros
+        .fetch_add(started.elapsed().as_micros() as u64, Ordering::Relaxed);
+    expired_total
+}
+
+/// Background task driving `active_expire_cycle` every `CYCLE_PERIOD`.
+pub async fn run(store: Arc<Store>, clock: Arc<dyn Clock>) {
+    let mut interval = tokio::time::interval(CYCLE_PERIOD);
+    loop {
+        interval.tick().await;
+        active_expire_cycle(&store, clock.as_ref(), CYCLE_BUDGET);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::clock::ManualClock;
+    use crate::internal::db::{Expiration, DEFAULT_DATABASES};
+
+    #[test]
+    fn volatile_keys_stay_indexed_after_removal() {
+        let mut keys = VolatileKeys::default();
+        for key in [b"a", b"b", b"c"] {
+            keys.insert(key);
+        }
+        keys.insert(b"a");
+        keys.remove(b"a");
+        keys.remove(b"missing");
+        assert_eq!(keys.len(), 2);
+        keys.remove(b"c");
+        assert_eq!(keys.random(), Some(&b"b"[..]));
+    }
+
+    #[test]
+    fn cycle_removes_expired_keys_nobody_reads() {
+        let store = Store::new(DEFAULT_DATABASES);
+        let clock = ManualClock::new();
+        {
+            let mut db = store.db(3);
+            db.set_time(clock.now());
+            for i in 0..500 {
+                let key = format!("stale:{}", i).into_bytes();
+                db.set(key, b"v".to_vec(), Some(Expiration::Seconds(1)));
+            }
+            db.set(
+                b"live".to_vec(),
+                b"v".to_vec(),
+                Some(Expiration::Seconds(60)),
+            );
+            db.set(b"plain".to_vec(), b"v".to_vec(), None);
+        }
+
+        assert_eq!(
+            active_expire_cycle(&store, &clock, Duration::from_secs(10)),
+            0
+        );
+        clock.advance(Duration::from_secs(1));
+        let expired = active_expire_cycle(&store, &clock, Duration::from_secs(10));
+
+        let db = store.db(3);
+        assert!(expired >= 450, "expired only {} keys", expired);
+        assert_eq!(db.expired_keys(), expired);
+        assert_eq!(db.len() as u64, 502 - expired);
+        assert_eq!(db.volatile_len() as u64, 501 - expired);
+    }
+
+    #[test]
+    fn cycle_stops_at_its_time_budget() {
+        let store = Store::new(DEFAULT_DATABASES);
+        let clock = ManualClock::new();
+        {
+            let mut db = store.db(0);
+            db.set_time(clock.now());
+            for i in 0..100 {
+                let key = format!("stale:{}", i).into_bytes();
+                db.set(key, b"v".to_vec(), Some(Expiration::Milliseconds(1)));
+            }
+        }
+        clock.advance(Duration::from_millis(1));
+
+        active_expire_cycle(&store, &clock, Duration::ZERO);
+
+        assert_eq!(store.expire_stats().time_cap_reached_count(), 1);
+        assert!(store.db(0).len() < 100);
+    }
+}
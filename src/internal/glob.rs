@@ -0,0 +1,121 @@
+/// Redis-style glob matching, as used by `KEYS` and `SCAN ... MATCH`.
+///
+/// Supports `*`, `?`, character classes such as `[abc]`, `[a-z]` and
+/// `[^x]`, and `\` to escape the next character, both inside and outside
+/// classes. An unterminated class runs to the end of the pattern.
+pub fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    // Where to resume after the most recent `*`: the pattern position just
+    // past it and the next string position it could swallow.
+    let mut backtrack: Option<(usize, usize)> = None;
+    loop {
+        if p < pattern.len() && pattern[p] == b'*' {
+            while p < pattern.len() && pattern[p] == b'*' {
+                p += 1;
+            }
+            if p == pattern.len() {
+                return true;
+            }
+            backtrack = Some((p, s));
+            continue;
+        }
+        if s == string.len() {
+            return pattern[p..].iter().all(|&c| c == b'*');
+        }
+        if p < pattern.len() {
+            let (matched, next) = match_one(pattern, p, string[s]);
+            if matched {
+                p = next;
+                s += 1;
+                continue;
+            }
+        }
+        match backtrack {
+            Some((star_p, star_s)) => {
+                p = star_p;
+                s = star_s + 1;
+                backtrack = Some((star_p, s));
+            }
+            None => return false,
+        }
+    }
+}
+
+/// Matches the single-character token at `pattern[p]` against `c` and
+/// returns whether it matched plus the position of the next token.
+fn match_one(pattern: &[u8], p: usize, c: u8) -> (bool, usize) {
+    match pattern[p] {
+        b'?' => (true, p + 1),
+        b'\\' if p + 1 < pattern.len() => (pattern[p + 1] == c, p + 2),
+        b'[' => {
+            let mut i = p + 1;
+            let negate = pattern.get(i) == Some(&b'^');
+            if negate {
+                i += 1;
+            }
+            let mut matched = false;
+            while i < pattern.len() {
+                match pattern[i] {
+                    b'\\' if i + 1 < pattern.len() => {
+                        matched |= pattern[i + 1] == c;
+                        i += 2;
+                    }
+                    b']' => {
+                        i += 1;
+                        break;
+                    }
+                    start if i + 2 < pattern.len() && pattern[i + 1] == b'-' => {
+                        let end = pattern[i + 2];
+                        let (low, high) = if start <= end {
+                            (start, end)
+                        } else {
+                            (end, start)
+                        };
+                        matched |= (low..=high).contains(&c);
+                        i += 3;
+                    }
+                    other => {
+                        matched |= other == c;
+                        i += 1;
+                    }
+                }
+            }
+            (matched != negate, i)
+        }
+        literal => (literal == c, p + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        assert!(glob_match(b"*", b""));
+        assert!(glob_match(b"user:*", b"user:42"));
+        assert!(!glob_match(b"user:*", b"session:42"));
+        assert!(glob_match(b"h?llo", b"hello"));
+        assert!(!glob_match(b"h?llo", b"hllo"));
+        assert!(glob_match(b"*a*b*c", b"xxaxxbxxbxc"));
+        assert!(!glob_match(b"*a*b*c", b"xxaxxbxxbx"));
+        assert!(glob_match(b"a**", b"abc"));
+    }
+
+    #[test]
+    fn classes_and_escapes() {
+        assert!(glob_match(b"h[ae]llo", b"hallo"));
+        assert!(!glob_match(b"h[ae]llo", b"hillo"));
+        assert!(glob_match(b"h[^e]llo", b"hallo"));
+        assert!(!glob_match(b"h[^e]llo", b"hello"));
+        assert!(glob_match(b"h[a-b]llo", b"hbllo"));
+        assert!(glob_match(b"h[b-a]llo", b"hallo"));
+        assert!(!glob_match(b"h[a-b]llo", b"hcllo"));
+        assert!(glob_match(b"[\\]]", b"]"));
+        assert!(glob_match(b"a\\*b", b"a*b"));
+        assert!(!glob_match(b"a\\*b", b"axb"));
+        assert!(glob_match(b"a\\?", b"a?"));
+        assert!(glob_match(b"[abc", b"b"));
+        assert!(glob_match(b"ab\\", b"ab\\"));
+    }
+}
@@ -1,3 +1,13 @@
+pub mod blocking;
+pub mod clock;
 pub mod cmd;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod expire;
+pub mod glob;
+pub mod random;
 pub mod resp;
+pub mod scan;
+pub mod session;
 pub mod traits;
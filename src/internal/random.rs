@@ -0,0 +1,30 @@
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+thread_local! {
+    static STATE: Cell<u64> = Cell::new(seed());
+}
+
+fn seed() -> u64 {
+    // RandomState is keyed from OS randomness, which is all we need here.
+    RandomState::new().build_hasher().finish() | 1
+}
+
+/// Next value of a per-thread xorshift64* generator. Not cryptographic;
+/// only used to pick sample keys and random elements.
+pub fn next_u64() -> u64 {
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    })
+}
+
+/// A uniformly distributed index in `0..bound`; `bound` must be non-zero.
+pub fn below(bound: usize) -> usize {
+    (next_u64() % bound as u64) as usize
+}
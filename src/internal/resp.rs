@@ -1,6 +1,8 @@
 use crate::internal::traits::RespVisitor;
 
-#[derive(Debug, Eq, PartialEq)]
+use bytes::{Buf, BytesMut};
+
+#[derive(Debug, PartialEq)]
 pub enum RespValue {
     SimpleString(String),
     Error(String),
@@ -8,6 +10,16 @@ pub enum RespValue {
     BulkString(Vec<u8>),
     Array(Vec<RespValue>),
     Null,
+    NullArray,
+    // RESP3-only types, downgraded by `encode` for RESP2 clients.
+    Map(Vec<(RespValue, RespValue)>),
+    Set(Vec<RespValue>),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(String),
+    Verbatim { format: String, data: Vec<u8> },
+    Push(Vec<RespValue>),
+    Attribute(Vec<(RespValue, RespValue)>, Box<RespValue>),
 }
 
 impl RespValue {
@@ -15,103 +27,530 @@ impl RespValue {
         match self {
             RespValue::Array(a) => visitor.visit_array(a),
             RespValue::BulkString(b) => visitor.visit_bulk_string(b),
-            _ => unimplemented!(),
+            other => visitor.visit_other(other),
         }
     }
-}
 
-impl std::fmt::Display for RespValue {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Serializes the value for a client speaking RESP `protocol` (2 or 3).
+    ///
+    /// RESP2 has no wire form for the RESP3 types, so they are downgraded
+    /// the way Redis does it: maps flatten to key/value arrays, sets and
+    /// pushes become arrays, booleans become integers, doubles, big numbers
+    /// and verbatim strings become bulk strings, and attributes are dropped.
+    pub fn encode(&self, protocol: u8, out: &mut Vec<u8>) {
+        let resp3 = protocol >= 3;
         match self {
-            RespValue::SimpleString(s) => {
-                write!(f, "+{}\r\n", s)
-            }
-            RespValue::Error(e) => {
-                write!(f, "-{}\r\n", e)
+            RespValue::SimpleString(s) => put_line(out, b'+', s.as_bytes()),
+            RespValue::Error(e) => put_line(out, b'-', e.as_bytes()),
+            RespValue::Integer(i) => put_line(out, b':', i.to_string().as_bytes()),
+            RespValue::BulkString(bytes) => put_bulk(out, b'$', bytes),
+            RespValue::Array(elements) => put_aggregate(out, b'*', elements, protocol),
+            RespValue::Null if resp3 => put_line(out, b'_', b""),
+            RespValue::Null => put_line(out, b'$', b"-1"),
+            RespValue::NullArray if resp3 => put_line(out, b'_', b""),
+            RespValue::NullArray => put_line(out, b'*', b"-1"),
+            RespValue::Map(pairs) => {
+                if resp3 {
+                    put_line(out, b'%', pairs.len().to_string().as_bytes());
+                } else {
+                    put_line(out, b'*', (pairs.len() * 2).to_string().as_bytes());
+                }
+                for (key, value) in pairs {
+                    key.encode(protocol, out);
+                    value.encode(protocol, out);
+                }
             }
-            RespValue::Integer(i) => {
-                write!(f, ":{}\r\n", i)
+            RespValue::Set(elements) if resp3 => put_aggregate(out, b'~', elements, protocol),
+            RespValue::Push(elements) if resp3 => put_aggregate(out, b'>', elements, protocol),
+            RespValue::Set(elements) | RespValue::Push(elements) => {
+                put_aggregate(out, b'*', elements, protocol)
             }
-            RespValue::BulkString(bytes) => {
-                write!(f, "${}\r\n", bytes.len())?;
-                let data_str = String::from_utf8_lossy(bytes);
-                write!(f, "{}\r\n", data_str)
+            RespValue::Double(d) if resp3 => put_line(out, b',', format_double(*d).as_bytes()),
+            RespValue::Double(d) => put_bulk(out, b'$', format_double(*d).as_bytes()),
+            RespValue::Boolean(b) if resp3 => put_line(out, b'#', if *b { b"t" } else { b"f" }),
+            RespValue::Boolean(b) => put_line(out, b':', if *b { b"1" } else { b"0" }),
+            RespValue::BigNumber(n) if resp3 => put_line(out, b'(', n.as_bytes()),
+            RespValue::BigNumber(n) => put_bulk(out, b'$', n.as_bytes()),
+            RespValue::Verbatim { format, data } if resp3 => {
+                let mut payload = Vec::with_capacity(format.len() + 1 + data.len());
+                payload.extend_from_slice(format.as_bytes());
+                payload.push(b':');
+                payload.extend_from_slice(data);
+                put_bulk(out, b'=', &payload);
             }
-            RespValue::Array(elements) => {
-                write!(f, "*{}\r\n", elements.len())?;
-                for element in elements {
-                    write!(f, "{}", element)?;
+            RespValue::Verbatim { data, .. } => put_bulk(out, b'$', data),
+            RespValue::Attribute(pairs, value) => {
+                if resp3 {
+                    put_line(out, b'|', pairs.len().to_string().as_bytes());
+                    for (key, value) in pairs {
+                        key.encode(protocol, out);
+                        value.encode(protocol, out);
+                    }
                 }
-                Ok(())
-            }
-            RespValue::Null => {
-                write!(f, "$-1\r\n")
+                value.encode(protocol, out);
             }
         }
     }
 }
 
-fn consume_crlf(data: &[u8], offset: &mut usize) -> Result<(), String> {
-    if data.len() < *offset + 2 || &data[*offset..*offset + 2] != b"\r\n" {
-        return Err(String::from("Missing CRLF terminator"));
+fn put_line(out: &mut Vec<u8>, prefix: u8, line: &[u8]) {
+    out.push(prefix);
+    out.extend_from_slice(line);
+    out.extend_from_slice(b"\r\n");
+}
+
+fn put_bulk(out: &mut Vec<u8>, prefix: u8, data: &[u8]) {
+    put_line(out, prefix, data.len().to_string().as_bytes());
+    out.extend_from_slice(data);
+    out.extend_from_slice(b"\r\n");
+}
+
+fn put_aggregate(out: &mut Vec<u8>, prefix: u8, elements: &[RespValue], protocol: u8) {
+    put_line(out, prefix, elements.len().to_string().as_bytes());
+    for element in elements {
+        element.encode(protocol, out);
+    }
+}
+
+/// Formats a double the way Redis writes it in replies (`inf`, `-inf`,
+/// `nan`, otherwise the shortest representation that round-trips).
+pub fn format_double(d: f64) -> String {
+    if d.is_nan() {
+        String::from("nan")
+    } else if d.is_infinite() {
+        String::from(if d > 0.0 { "inf" } else { "-inf" })
+    } else {
+        d.to_string()
+    }
+}
+
+/// Why a frame could not be decoded from the bytes at hand.
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum RespError {
+    /// The buffer holds only a prefix of a frame; read more and retry.
+    #[error("Incomplete frame")]
+    Incomplete,
+    #[error("{0}")]
+    Invalid(String),
+}
+
+fn consume_crlf(data: &[u8], offset: &mut usize) -> Result<(), RespError> {
+    if data.len() < *offset + 2 {
+        return Err(RespError::Incomplete);
+    }
+    if &data[*offset..*offset + 2] != b"\r\n" {
+        return Err(RespError::Invalid(String::from("Missing CRLF terminator")));
     }
     *offset += 2;
     Ok(())
 }
 
-fn parse_integer(data: &[u8], offset: &mut usize) -> Result<i64, String> {
+/// Reads a CRLF-terminated header line. Like Redis, a line longer than
+/// `INLINE_MAX_SIZE` is rejected with `too_big` as soon as that many bytes
+/// are buffered, without waiting for its end.
+fn read_line<'a>(data: &'a [u8], offset: &mut usize, too_big: &str) -> Result<&'a [u8], RespError> {
     let start = *offset;
-    while *offset < data.len() && data[*offset] != b'\r' {
+    let end = data.len().min(start + INLINE_MAX_SIZE + 1);
+    while *offset < end && data[*offset] != b'\r' {
         *offset += 1;
     }
-    let num_bytes = &data[start..*offset];
-    let num_str = std::str::from_utf8(num_bytes).map_err(|_| String::from("Invalid utf-8"))?;
+    if *offset - start > INLINE_MAX_SIZE {
+        return Err(RespError::Invalid(too_big.to_owned()));
+    }
+    if *offset == data.len() {
+        return Err(RespError::Incomplete);
+    }
+    let line = &data[start..*offset];
+    consume_crlf(data, offset)?;
+    Ok(line)
+}
+
+fn read_line_str<'a>(
+    data: &'a [u8],
+    offset: &mut usize,
+    too_big: &str,
+) -> Result<&'a str, RespError> {
+    let line = read_line(data, offset, too_big)?;
+    std::str::from_utf8(line).map_err(|_| RespError::Invalid(String::from("Invalid utf-8")))
+}
 
-    let number = num_str
+fn parse_integer(data: &[u8], offset: &mut usize, too_big: &str) -> Result<i64, RespError> {
+    read_line_str(data, offset, too_big)?
         .parse::<i64>()
-        .map_err(|_| format!("Invalid integer format, {}", num_str))?;
+        .map_err(|_| RespError::Invalid(String::from("Invalid integer format")))
+}
 
-    consume_crlf(data, offset)?;
-    Ok(number)
+/// Bounds on what a peer may ask the parser to buffer or allocate.
+#[derive(Clone, Debug)]
+pub struct ProtoLimits {
+    /// Largest accepted bulk string (Redis' `proto-max-bulk-len`).
+    pub max_bulk_len: usize,
+    /// Largest accepted element count of a single aggregate.
+    pub max_multibulk_len: usize,
+    /// Deepest accepted nesting of aggregates.
+    pub max_depth: usize,
+    /// Most bytes a client may have buffered without completing a frame.
+    pub max_query_buffer: usize,
 }
 
-fn parse_bulk_string(data: &[u8], offset: &mut usize) -> Result<RespValue, String> {
-    if data.get(*offset) != Some(&b'$') {
-        return Err(String::from("Expected bulk string prefix '$'"));
+impl Default for ProtoLimits {
+    fn default() -> Self {
+        ProtoLimits {
+            max_bulk_len: 512 * 1024 * 1024,
+            max_multibulk_len: 1024 * 1024,
+            max_depth: 32,
+            max_query_buffer: 1024 * 1024 * 1024,
+        }
     }
-    *offset += 1;
-    let length = parse_integer(data, offset)?;
+}
+
+/// Largest inline command line, as in Redis' `PROTO_INLINE_MAX_SIZE`.
+const INLINE_MAX_SIZE: usize = 64 * 1024;
 
+/// Error for an overlong line that carries no count, e.g. a simple string.
+const TOO_BIG_LINE: &str = "too big line";
+
+/// Upper bound on capacity reserved up front for an aggregate, so a large
+/// declared length only costs memory once its elements actually arrive.
+const MAX_PREALLOCATED_ELEMENTS: usize = 1024;
+
+fn parse_blob<'a>(
+    data: &'a [u8],
+    offset: &mut usize,
+    limits: &ProtoLimits,
+) -> Result<Option<&'a [u8]>, RespError> {
+    let length = parse_integer(data, offset, "too big bulk count string")?;
     if length < 0 {
-        return Ok(RespValue::Null);
+        return Ok(None);
+    }
+    if length as u64 > limits.max_bulk_len as u64 {
+        return Err(RespError::Invalid(String::from("invalid bulk length")));
     }
     let length = length as usize;
     if data.len() < *offset + length {
-        return Err(String::from("Incomplete bulk string data"));
+        return Err(RespError::Incomplete);
     }
-    let bulk_data = data[*offset..*offset + length].to_vec();
+    let blob = &data[*offset..*offset + length];
     *offset += length;
     consume_crlf(data, offset)?;
-    Ok(RespValue::BulkString(bulk_data))
+    Ok(Some(blob))
+}
+
+fn parse_aggregate_len(
+    data: &[u8],
+    offset: &mut usize,
+    limits: &ProtoLimits,
+    depth: usize,
+) -> Result<i64, RespError> {
+    if depth >= limits.max_depth {
+        return Err(RespError::Invalid(String::from(
+            "too many nested aggregates",
+        )));
+    }
+    let count = parse_integer(data, offset, "too big mbulk count string")?;
+    if count > limits.max_multibulk_len as i64 {
+        return Err(RespError::Invalid(String::from("invalid multibulk length")));
+    }
+    Ok(count)
+}
+
+fn parse_elements(
+    data: &[u8],
+    offset: &mut usize,
+    count: i64,
+    limits: &ProtoLimits,
+    depth: usize,
+) -> Result<Vec<RespValue>, RespError> {
+    let mut elements = Vec::with_capacity((count.max(0) as usize).min(MAX_PREALLOCATED_ELEMENTS));
+    for _ in 0..count {
+        elements.push(parse_value(data, offset, limits, depth + 1)?);
+    }
+    Ok(elements)
+}
+
+fn parse_pairs(
+    data: &[u8],
+    offset: &mut usize,
+    limits: &ProtoLimits,
+    depth: usize,
+) -> Result<Vec<(RespValue, RespValue)>, RespError> {
+    let count = parse_aggregate_len(data, offset, limits, depth)?;
+    let mut pairs = Vec::with_capacity((count.max(0) as usize).min(MAX_PREALLOCATED_ELEMENTS));
+    for _ in 0..count {
+        let key = parse_value(data, offset, limits, depth + 1)?;
+        let value = parse_value(data, offset, limits, depth + 1)?;
+        pairs.push((key, value));
+    }
+    Ok(pairs)
+}
+
+fn parse_double(data: &[u8], offset: &mut usize) -> Result<RespValue, RespError> {
+    let line = read_line_str(data, offset, TOO_BIG_LINE)?;
+    let value = match line {
+        "inf" => f64::INFINITY,
+        "-inf" => f64::NEG_INFINITY,
+        "nan" => f64::NAN,
+        _ => line
+            .parse::<f64>()
+            .map_err(|_| RespError::Invalid(String::from("Invalid double format")))?,
+    };
+    Ok(RespValue::Double(value))
+}
+
+fn parse_verbatim(
+    data: &[u8],
+    offset: &mut usize,
+    limits: &ProtoLimits,
+) -> Result<RespValue, RespError> {
+    let invalid = || RespError::Invalid(String::from("Invalid verbatim string"));
+    let payload = parse_blob(data, offset, limits)?.ok_or_else(invalid)?;
+    if payload.len() < 4 || payload[3] != b':' {
+        return Err(invalid());
+    }
+    let format = std::str::from_utf8(&payload[..3]).map_err(|_| invalid())?;
+    Ok(RespValue::Verbatim {
+        format: format.to_owned(),
+        data: payload[4..].to_vec(),
+    })
+}
+
+fn parse_bulk_string(
+    data: &[u8],
+    offset: &mut usize,
+    limits: &ProtoLimits,
+) -> Result<RespValue, RespError> {
+    if data.get(*offset) != Some(&b'$') {
+        return Err(RespError::Invalid(String::from(
+            "Expected bulk string prefix '$'",
+        )));
+    }
+    *offset += 1;
+    match parse_blob(data, offset, limits)? {
+        Some(blob) => Ok(RespValue::BulkString(blob.to_vec())),
+        None => Ok(RespValue::Null),
+    }
+}
+
+/// Parses one value under the default [`ProtoLimits`].
+#[cfg(test)]
+pub fn parse(data: &[u8], offset: &mut usize) -> Result<RespValue, RespError> {
+    parse_value(data, offset, &ProtoLimits::default(), 0)
 }
 
-pub fn parse(data: &[u8], offset: &mut usize) -> Result<RespValue, String> {
-    match data.get(*offset).ok_or("Empty data")? {
+/// Parses one value whose enclosing aggregates are `depth` levels deep.
+fn parse_value(
+    data: &[u8],
+    offset: &mut usize,
+    limits: &ProtoLimits,
+    depth: usize,
+) -> Result<RespValue, RespError> {
+    let prefix = *data.get(*offset).ok_or(RespError::Incomplete)?;
+    if prefix == b'$' {
+        return parse_bulk_string(data, offset, limits);
+    }
+    *offset += 1;
+    match prefix {
         b'*' => {
-            *offset += 1;
-            let num_elements = parse_integer(data, offset)?;
+            let num_elements = parse_aggregate_len(data, offset, limits, depth)?;
+            if num_elements < 0 {
+                return Ok(RespValue::NullArray);
+            }
+            Ok(RespValue::Array(parse_elements(
+                data,
+                offset,
+                num_elements,
+                limits,
+                depth,
+            )?))
+        }
+        b'+' => Ok(RespValue::SimpleString(
+            read_line_str(data, offset, TOO_BIG_LINE)?.to_owned(),
+        )),
+        b'-' => Ok(RespValue::Error(
+            read_line_str(data, offset, TOO_BIG_LINE)?.to_owned(),
+        )),
+        b':' => Ok(RespValue::Integer(parse_integer(
+            data,
+            offset,
+            TOO_BIG_LINE,
+        )?)),
+        b'_' => {
+            read_line(data, offset, TOO_BIG_LINE)?;
+            Ok(RespValue::Null)
+        }
+        b'#' => match read_line(data, offset, TOO_BIG_LINE)? {
+            b"t" => Ok(RespValue::Boolean(true)),
+            b"f" => Ok(RespValue::Boolean(false)),
+            _ => Err(RespError::Invalid(String::from("Invalid boolean"))),
+        },
+        b',' => parse_double(data, offset),
+        b'(' => Ok(RespValue::BigNumber(
+            read_line_str(data, offset, TOO_BIG_LINE)?.to_owned(),
+        )),
+        b'=' => parse_verbatim(data, offset, limits),
+        b'%' => Ok(RespValue::Map(parse_pairs(data, offset, limits, depth)?)),
+        b'|' => {
+            let attributes = parse_pairs(data, offset, limits, depth)?;
+            let value = parse_value(data, offset, limits, depth)?;
+            Ok(RespValue::Attribute(attributes, Box::new(value)))
+        }
+        b'~' | b'>' => {
+            let count = parse_aggregate_len(data, offset, limits, depth)?;
+            let elements = parse_elements(data, offset, count, limits, depth)?;
+            if prefix == b'~' {
+                Ok(RespValue::Set(elements))
+            } else {
+                Ok(RespValue::Push(elements))
+            }
+        }
+        _ => Err(RespError::Invalid(String::from(
+            "Unsupported or invalid RESP prefix",
+        ))),
+    }
+}
 
-            if num_elements < 1 {
-                return Err(String::from("Expected a command array of length atleast 1"));
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Splits an inline command line into arguments using the same rules as
+/// Redis' `sdssplitargs`: whitespace separates arguments, double quotes
+/// understand `\n`, `\r`, `\t`, `\b`, `\a` and `\xHH` escapes, single quotes
+/// only `\'`, and a closing quote must be followed by whitespace.
+fn split_inline_args(line: &[u8]) -> Result<Vec<Vec<u8>>, RespError> {
+    let unbalanced = || RespError::Invalid(String::from("unbalanced quotes in request"));
+    let mut args = vec![];
+    let mut pos = 0;
+    loop {
+        while pos < line.len() && line[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if pos == line.len() {
+            return Ok(args);
+        }
+
+        let mut current = vec![];
+        let mut in_double = false;
+        let mut in_single = false;
+        loop {
+            let byte = line.get(pos).copied();
+            if in_double {
+                match byte {
+                    None => return Err(unbalanced()),
+                    Some(b'\\') if pos + 1 < line.len() => {
+                        let hex = match (line[pos + 1], line.get(pos + 2), line.get(pos + 3)) {
+                            (b'x', Some(&hi), Some(&lo)) => hex_digit(hi).zip(hex_digit(lo)),
+                            _ => None,
+                        };
+                        if let Some((hi, lo)) = hex {
+                            current.push(hi * 16 + lo);
+                            pos += 3;
+                        } else {
+                            pos += 1;
+                            current.push(match line[pos] {
+                                b'n' => b'\n',
+                                b'r' => b'\r',
+                                b't' => b'\t',
+                                b'b' => 0x08,
+                                b'a' => 0x07,
+                                other => other,
+                            });
+                        }
+                    }
+                    Some(b'"') => {
+                        if line.get(pos + 1).is_some_and(|b| !b.is_ascii_whitespace()) {
+                            return Err(unbalanced());
+                        }
+                        pos += 1;
+                        break;
+                    }
+                    Some(other) => current.push(other),
+                }
+            } else if in_single {
+                match byte {
+                    None => return Err(unbalanced()),
+                    Some(b'\\') if line.get(pos + 1) == Some(&b'\'') => {
+                        current.push(b'\'');
+                        pos += 1;
+                    }
+                    Some(b'\'') => {
+                        if line.get(pos + 1).is_some_and(|b| !b.is_ascii_whitespace()) {
+                            return Err(unbalanced());
+                        }
+                        pos += 1;
+                        break;
+                    }
+                    Some(other) => current.push(other),
+                }
+            } else {
+                match byte {
+                    None => break,
+                    Some(b) if b.is_ascii_whitespace() => break,
+                    Some(b'"') => in_double = true,
+                    Some(b'\'') => in_single = true,
+                    Some(other) => current.push(other),
+                }
             }
-            let mut elements = Vec::with_capacity(num_elements as usize);
-            for _ in 0..num_elements {
-                elements.push(parse(&data, offset)?);
+            pos += 1;
+        }
+        args.push(current);
+    }
+}
+
+/// Parses one newline-terminated inline command (`SET foo bar\r\n`), as
+/// typed into `telnet` or `nc`, into the same array of bulk strings a
+/// RESP client would send.
+fn parse_inline(data: &[u8], offset: &mut usize) -> Result<RespValue, RespError> {
+    let rest = &data[*offset..];
+    let too_big = || RespError::Invalid(String::from("too big inline request"));
+    let newline = match rest.iter().position(|&b| b == b'\n') {
+        Some(newline) if newline > INLINE_MAX_SIZE => return Err(too_big()),
+        Some(newline) => newline,
+        None if rest.len() > INLINE_MAX_SIZE => return Err(too_big()),
+        None => return Err(RespError::Incomplete),
+    };
+    let line = rest[..newline]
+        .strip_suffix(b"\r")
+        .unwrap_or(&rest[..newline]);
+    let args = split_inline_args(line)?;
+    *offset += newline + 1;
+    Ok(RespValue::Array(
+        args.into_iter().map(RespValue::BulkString).collect(),
+    ))
+}
+
+/// Decodes the next complete command at the front of `buffer`.
+///
+/// Commands starting with `*` are parsed as RESP, anything else as an
+/// inline command; blank inline lines are skipped. On success the frame's
+/// bytes are consumed and anything after it (the start of a pipelined
+/// command) stays in the buffer. `Ok(None)` means the buffer holds only
+/// part of a frame and nothing was consumed; a partial frame that already
+/// exceeds `limits.max_query_buffer` is rejected instead.
+pub fn decode(buffer: &mut BytesMut, limits: &ProtoLimits) -> Result<Option<RespValue>, RespError> {
+    loop {
+        let mut offset = 0;
+        let result = match buffer.first() {
+            None => return Ok(None),
+            Some(b'*') => parse_value(buffer, &mut offset, limits, 0),
+            Some(_) => parse_inline(buffer, &mut offset),
+        };
+        match result {
+            // Like Redis, empty and null multibulks are skipped, not executed.
+            Ok(RespValue::Array(args)) if args.is_empty() => buffer.advance(offset),
+            Ok(RespValue::NullArray) => buffer.advance(offset),
+            Ok(value) => {
+                buffer.advance(offset);
+                return Ok(Some(value));
+            }
+            Err(RespError::Incomplete) if buffer.len() > limits.max_query_buffer => {
+                return Err(RespError::Invalid(String::from(
+                    "client query buffer limit exceeded",
+                )))
             }
-            return Ok(RespValue::Array(elements));
+            Err(RespError::Incomplete) => return Ok(None),
+            Err(e) => return Err(e),
         }
-        b'$' => parse_bulk_string(data, offset),
-        _ => Err(String::from("Unsupported or invalid RESP prefix")),
     }
 }
 
@@ -254,4 +693,379 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn decode_waits_for_split_frame() {
+        let mut buffer = BytesMut::from(&b"*2\r\n$4\r\nECHO\r\n$3\r\nh"[..]);
+        assert_eq!(decode(&mut buffer, &ProtoLimits::default()), Ok(None));
+        assert_eq!(buffer.len(), 19);
+
+        buffer.extend_from_slice(b"ey\r\n");
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            Ok(Some(RespValue::Array(vec![
+                RespValue::BulkString(b"ECHO".to_vec()),
+                RespValue::BulkString(b"hey".to_vec()),
+            ])))
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn decode_pipelined_frames_in_order() {
+        let mut buffer =
+            BytesMut::from(&b"*1\r\n$4\r\nPING\r\n*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n*1\r\n$4"[..]);
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            Ok(Some(RespValue::Array(vec![RespValue::BulkString(
+                b"PING".to_vec()
+            )])))
+        );
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            Ok(Some(RespValue::Array(vec![
+                RespValue::BulkString(b"GET".to_vec()),
+                RespValue::BulkString(b"foo".to_vec()),
+            ])))
+        );
+        assert_eq!(decode(&mut buffer, &ProtoLimits::default()), Ok(None));
+        assert_eq!(&buffer[..], b"*1\r\n$4");
+    }
+
+    #[test]
+    fn decode_bulk_string_larger_than_read_size() {
+        let value = vec![b'x'; 4096];
+        let mut buffer = BytesMut::new();
+        buffer.extend_from_slice(b"*3\r\n$3\r\nSET\r\n$3\r\nbig\r\n$4096\r\n");
+        buffer.extend_from_slice(&value);
+        buffer.extend_from_slice(b"\r\n");
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            Ok(Some(RespValue::Array(vec![
+                RespValue::BulkString(b"SET".to_vec()),
+                RespValue::BulkString(b"big".to_vec()),
+                RespValue::BulkString(value),
+            ])))
+        );
+    }
+
+    #[test]
+    fn decode_rejects_malformed_frame() {
+        let mut buffer = BytesMut::from(&b"*1\r\n$4\r\nPINGxx"[..]);
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            Err(RespError::Invalid(String::from("Missing CRLF terminator")))
+        );
+    }
+
+    fn inline(line: &str) -> Result<Option<RespValue>, RespError> {
+        decode(
+            &mut BytesMut::from(line.as_bytes()),
+            &ProtoLimits::default(),
+        )
+    }
+
+    fn bulk_array(args: &[&[u8]]) -> RespValue {
+        RespValue::Array(
+            args.iter()
+                .map(|a| RespValue::BulkString(a.to_vec()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn decode_inline_command() {
+        assert_eq!(inline("PING\r\n"), Ok(Some(bulk_array(&[b"PING"]))));
+        assert_eq!(
+            inline("  SET   foo\tbar\n"),
+            Ok(Some(bulk_array(&[b"SET", b"foo", b"bar"])))
+        );
+        assert_eq!(inline("SET foo"), Ok(None));
+    }
+
+    #[test]
+    fn decode_inline_skips_blank_lines() {
+        let mut buffer = BytesMut::from(&b"\r\n   \nPING\r\n"[..]);
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            Ok(Some(bulk_array(&[b"PING"])))
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn decode_inline_quoting() {
+        assert_eq!(
+            inline("SET \"hello world\" 'it\\'s'\r\n"),
+            Ok(Some(bulk_array(&[b"SET", b"hello world", b"it's"])))
+        );
+        assert_eq!(
+            inline("ECHO \"a\\tb\\x41\\\"\" \"\"\r\n"),
+            Ok(Some(bulk_array(&[b"ECHO", b"a\tbA\"", b""])))
+        );
+        assert_eq!(
+            inline("ECHO 'no\\nescape'\r\n"),
+            Ok(Some(bulk_array(&[b"ECHO", b"no\\nescape"])))
+        );
+    }
+
+    #[test]
+    fn decode_inline_unbalanced_quotes() {
+        let unbalanced = Err(RespError::Invalid(String::from(
+            "unbalanced quotes in request",
+        )));
+        assert_eq!(inline("ECHO \"open\r\n"), unbalanced);
+        assert_eq!(inline("ECHO \"a\"b\r\n"), unbalanced);
+        assert_eq!(inline("ECHO 'open\r\n"), unbalanced);
+    }
+
+    fn encoded(value: &RespValue, protocol: u8) -> Vec<u8> {
+        let mut out = vec![];
+        value.encode(protocol, &mut out);
+        out
+    }
+
+    fn sample_map() -> RespValue {
+        RespValue::Map(vec![
+            (
+                RespValue::BulkString(b"proto".to_vec()),
+                RespValue::Integer(3),
+            ),
+            (
+                RespValue::BulkString(b"ok".to_vec()),
+                RespValue::Boolean(true),
+            ),
+        ])
+    }
+
+    #[test]
+    fn encode_resp3_types() {
+        assert_eq!(
+            encoded(&sample_map(), 3),
+            b"%2\r\n$5\r\nproto\r\n:3\r\n$2\r\nok\r\n#t\r\n".to_vec()
+        );
+        assert_eq!(encoded(&RespValue::Null, 3), b"_\r\n".to_vec());
+        assert_eq!(encoded(&RespValue::Double(1.5), 3), b",1.5\r\n".to_vec());
+        assert_eq!(
+            encoded(&RespValue::Double(f64::NEG_INFINITY), 3),
+            b",-inf\r\n".to_vec()
+        );
+        assert_eq!(
+            encoded(
+                &RespValue::Verbatim {
+                    format: String::from("txt"),
+                    data: b"hi".to_vec(),
+                },
+                3
+            ),
+            b"=6\r\ntxt:hi\r\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn encode_downgrades_to_resp2() {
+        assert_eq!(
+            encoded(&sample_map(), 2),
+            b"*4\r\n$5\r\nproto\r\n:3\r\n$2\r\nok\r\n:1\r\n".to_vec()
+        );
+        assert_eq!(encoded(&RespValue::Null, 2), b"$-1\r\n".to_vec());
+        assert_eq!(
+            encoded(&RespValue::Double(1.5), 2),
+            b"$3\r\n1.5\r\n".to_vec()
+        );
+        assert_eq!(
+            encoded(&RespValue::BigNumber(String::from("123")), 2),
+            b"$3\r\n123\r\n".to_vec()
+        );
+        assert_eq!(
+            encoded(
+                &RespValue::Attribute(
+                    vec![(
+                        RespValue::BulkString(b"ttl".to_vec()),
+                        RespValue::Integer(1)
+                    )],
+                    Box::new(RespValue::Set(vec![RespValue::Integer(7)]))
+                ),
+                2
+            ),
+            b"*1\r\n:7\r\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn parse_resp3_round_trip() {
+        let values = vec![
+            sample_map(),
+            RespValue::Null,
+            RespValue::Boolean(false),
+            RespValue::Double(-2.25),
+            RespValue::BigNumber(String::from("3492890328409238509324850943850943825024385")),
+            RespValue::Verbatim {
+                format: String::from("mkd"),
+                data: b"# title".to_vec(),
+            },
+            RespValue::Set(vec![RespValue::BulkString(b"a".to_vec())]),
+            RespValue::Push(vec![RespValue::BulkString(b"message".to_vec())]),
+            RespValue::Attribute(
+                vec![(
+                    RespValue::BulkString(b"key".to_vec()),
+                    RespValue::Double(0.5),
+                )],
+                Box::new(RespValue::BulkString(b"value".to_vec())),
+            ),
+        ];
+        for value in values {
+            let bytes = encoded(&value, 3);
+            let mut offset = 0;
+            assert_eq!(parse(&bytes, &mut offset), Ok(value));
+            assert_eq!(offset, bytes.len());
+        }
+    }
+
+    #[test]
+    fn limits_reject_oversized_frames() {
+        let limits = ProtoLimits {
+            max_bulk_len: 8,
+            max_multibulk_len: 4,
+            max_depth: 2,
+            max_query_buffer: 1024,
+        };
+        let invalid = |msg: &str| Err(RespError::Invalid(String::from(msg)));
+
+        let mut buffer = BytesMut::from(&b"*2147483647\r\n"[..]);
+        assert_eq!(
+            decode(&mut buffer, &limits),
+            invalid("invalid multibulk length")
+        );
+
+        let mut buffer = BytesMut::from(&b"*1\r\n$9\r\n"[..]);
+        assert_eq!(decode(&mut buffer, &limits), invalid("invalid bulk length"));
+
+        let mut buffer = BytesMut::from(&b"*1\r\n*1\r\n*1\r\n$1\r\na\r\n"[..]);
+        assert_eq!(
+            decode(&mut buffer, &limits),
+            invalid("too many nested aggregates")
+        );
+
+        let limits = ProtoLimits {
+            max_query_buffer: 64,
+            ..ProtoLimits::default()
+        };
+        let mut buffer = BytesMut::from(&b"*1\r\n$100\r\n"[..]);
+        assert_eq!(decode(&mut buffer, &limits), Ok(None));
+        buffer.extend_from_slice(&[b' '; 64]);
+        assert_eq!(
+            decode(&mut buffer, &limits),
+            invalid("client query buffer limit exceeded")
+        );
+    }
+
+    #[test]
+    fn limits_reject_oversized_inline_request() {
+        let mut buffer = BytesMut::from(&vec![b'a'; INLINE_MAX_SIZE + 1][..]);
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            Err(RespError::Invalid(String::from("too big inline request")))
+        );
+    }
+
+    #[test]
+    fn limits_reject_oversized_count_line_before_its_end() {
+        let invalid = |msg: &str| Err(RespError::Invalid(String::from(msg)));
+        let digits = vec![b'1'; INLINE_MAX_SIZE + 1];
+        let mut buffer = BytesMut::from(&b"*"[..]);
+        buffer.extend_from_slice(&digits);
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            invalid("too big mbulk count string")
+        );
+        let mut buffer = BytesMut::from(&b"*1\r\n$"[..]);
+        buffer.extend_from_slice(&digits);
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            invalid("too big bulk count string")
+        );
+        let mut buffer = BytesMut::from(&b"*1\r\n$"[..]);
+        buffer.extend_from_slice(&digits[..INLINE_MAX_SIZE - 1]);
+        assert_eq!(decode(&mut buffer, &ProtoLimits::default()), Ok(None));
+        buffer.extend_from_slice(b"x\r\n");
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            invalid("Invalid integer format")
+        );
+    }
+
+    #[test]
+    fn parse_resp2_scalars() {
+        let mut offset = 0;
+        assert_eq!(
+            parse(b"+OK\r\n", &mut offset),
+            Ok(RespValue::SimpleString(String::from("OK")))
+        );
+        let mut offset = 0;
+        assert_eq!(
+            parse(b"-ERR unknown command\r\n", &mut offset),
+            Ok(RespValue::Error(String::from("ERR unknown command")))
+        );
+        let mut offset = 0;
+        assert_eq!(parse(b":-42\r\n", &mut offset), Ok(RespValue::Integer(-42)));
+        let mut offset = 0;
+        assert_eq!(parse(b"$-1\r\n", &mut offset), Ok(RespValue::Null));
+    }
+
+    #[test]
+    fn parse_null_empty_and_nested_arrays() {
+        let mut offset = 0;
+        assert_eq!(parse(b"*-1\r\n", &mut offset), Ok(RespValue::NullArray));
+        let mut offset = 0;
+        assert_eq!(parse(b"*0\r\n", &mut offset), Ok(RespValue::Array(vec![])));
+        let mut offset = 0;
+        assert_eq!(
+            parse(b"*2\r\n*1\r\n:1\r\n*0\r\n", &mut offset),
+            Ok(RespValue::Array(vec![
+                RespValue::Array(vec![RespValue::Integer(1)]),
+                RespValue::Array(vec![]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_resp2_round_trip() {
+        let values = vec![
+            RespValue::SimpleString(String::from("PONG")),
+            RespValue::Error(String::from("WRONGTYPE Operation against a key")),
+            RespValue::Integer(i64::MIN),
+            RespValue::BulkString(vec![0, 1, 2, b'\r', b'\n']),
+            RespValue::BulkString(vec![]),
+            RespValue::Null,
+            RespValue::NullArray,
+            RespValue::Array(vec![]),
+            RespValue::Array(vec![
+                RespValue::Integer(1),
+                RespValue::Array(vec![
+                    RespValue::SimpleString(String::from("nested")),
+                    RespValue::Null,
+                    RespValue::NullArray,
+                ]),
+                RespValue::BulkString(b"tail".to_vec()),
+            ]),
+        ];
+        for value in values {
+            let bytes = encoded(&value, 2);
+            let mut offset = 0;
+            assert_eq!(parse(&bytes, &mut offset), Ok(value));
+            assert_eq!(offset, bytes.len());
+        }
+    }
+
+    #[test]
+    fn decode_skips_empty_multibulk() {
+        let mut buffer = BytesMut::from(&b"*0\r\n*-1\r\n*1\r\n$4\r\nPING\r\n"[..]);
+        assert_eq!(
+            decode(&mut buffer, &ProtoLimits::default()),
+            Ok(Some(RespValue::Array(vec![RespValue::BulkString(
+                b"PING".to_vec()
+            )])))
+        );
+    }
 }
@@ -0,0 +1,206 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Bound;
+
+/// Where `item` sits in the order every cursor-based scan walks.
+///
+/// `DefaultHasher::new()` uses fixed keys, so the position of an item
+/// never changes while the server runs, whatever happens to the
+/// collection holding it.
+fn scan_position(item: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A key together with its scan position, which it is sorted by first.
+type Slot = (u64, Vec<u8>);
+
+/// A map keyed by byte strings and stored in scan order, so a `SCAN`-style
+/// page resumes from its cursor without walking the whole collection.
+#[derive(Clone)]
+pub struct ScanMap<V> {
+    entries: BTreeMap<Slot, V>,
+}
+
+impl<V> Default for ScanMap<V> {
+    fn default() -> Self {
+        ScanMap {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+/// Every slot a key at `position` can occupy. An empty `Vec` sorts first
+/// and does not allocate, so lookups need no owned copy of the key.
+fn slots_at(position: u64) -> (Bound<Slot>, Bound<Slot>) {
+    let end = match position.checked_add(1) {
+        Some(next) => Bound::Excluded((next, Vec::new())),
+        None => Bound::Unbounded,
+    };
+    (Bound::Included((position, Vec::new())), end)
+}
+
+impl<V> ScanMap<V> {
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        self.entries
+            .range(slots_at(scan_position(key)))
+            .find(|((_, existing), _)| existing == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        self.entries
+            .range_mut(slots_at(scan_position(key)))
+            .find(|((_, existing), _)| existing == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Stores `value` under `key`, handing back the value it replaced.
+    pub fn insert(&mut self, key: Vec<u8>, value: V) -> Option<V> {
+        self.entries.insert((scan_position(&key), key), value)
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        self.entries.remove(&(scan_position(key), key.to_vec()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, &V)> {
+        self.entries.iter().map(|((_, key), value)| (key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Vec<u8>> {
+        self.entries.keys().map(|(_, key)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.values()
+    }
+
+    /// One page of a `SCAN`-style iteration.
+    ///
+    /// The cursor is the scan position to resume from, so a key that is
+    /// present for the whole iteration is returned exactly once no matter
+    /// how the map grows or shrinks in between. Keys sharing a position
+    /// are always returned together, which can make a page slightly
+    /// larger than `count`. The returned cursor is `0` once the walk is
+    /// done.
+    pub fn page(&self, cursor: u64, count: usize) -> (u64, Vec<&[u8]>) {
+        let count = count.max(1);
+        let mut page = Vec::new();
+        let mut last = None;
+        for ((position, key), _) in self.entries.range((cursor, Vec::new())..) {
+            if page.len() >= count && last != Some(*position) {
+                // Resume at the first position this page left out.
+                return (*position, page);
+            }
+            page.push(key.as_slice());
+            last = Some(*position);
+        }
+        (0, page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn pages_cover_every_item_once() {
+        let items: Vec<Vec<u8>> = (0..1000)
+            .map(|i| format!("key:{}", i).into_bytes())
+            .collect();
+        let mut map = ScanMap::default();
+        for item in &items {
+            map.insert(item.clone(), ());
+        }
+        let mut seen = HashSet::new();
+        let mut cursor = 0;
+        loop {
+            let (next, page) = map.page(cursor, 7);
+            assert!(page.len() >= 7 || next == 0);
+            for item in page {
+                assert!(seen.insert(item.to_vec()), "returned twice");
+            }
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        assert_eq!(seen.len(), items.len());
+    }
+
+    #[test]
+    fn items_present_throughout_survive_growth_and_removal() {
+        let stable: Vec<Vec<u8>> = (0..200)
+            .map(|i| format!("stable:{}", i).into_bytes())
+            .collect();
+        let doomed: Vec<Vec<u8>> = (0..200)
+            .map(|i| format!("doomed:{}", i).into_bytes())
+            .collect();
+        let mut map = ScanMap::default();
+        for item in stable.iter().chain(&doomed) {
+            map.insert(item.clone(), ());
+        }
+        let mut seen = HashSet::new();
+        let mut cursor = 0;
+        let mut round = 0;
+        loop {
+            let (next, page) = map.page(cursor, 20);
+            seen.extend(page.into_iter().map(<[u8]>::to_vec));
+            // Churn the collection between calls.
+            if round % 2 == 1 {
+                for item in &doomed {
+                    map.remove(item);
+                }
+            }
+            for i in 0..5 {
+                map.insert(format!("new:{}:{}", round, i).into_bytes(), ());
+            }
+            round += 1;
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        assert!(stable.iter().all(|item| seen.contains(item)));
+    }
+
+    #[test]
+    fn lookups_find_inserted_keys() {
+        let mut map = ScanMap::default();
+        assert_eq!(map.insert(b"a".to_vec(), 1), None);
+        assert_eq!(map.insert(b"a".to_vec(), 2), Some(1));
+        map.insert(b"b".to_vec(), 3);
+        *map.get_mut(b"b").unwrap() += 1;
+        assert_eq!(
+            (map.get(b"a"), map.get(b"b"), map.get(b"c")),
+            (Some(&2), Some(&4), None)
+        );
+        assert_eq!(map.remove(b"a"), Some(2));
+        assert_eq!(map.remove(b"a"), None);
+        assert_eq!(map.page(0, 10), (0, vec![&b"b"[..]]));
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.page(0, 10), (0, vec![]));
+    }
+}
@@ -0,0 +1,18 @@
+/// Per-connection state that must not leak between clients.
+pub struct Session {
+    pub id: u64,
+    pub db: usize,
+    pub name: Option<String>,
+    pub protocol: u8,
+}
+
+impl Session {
+    pub fn new(id: u64) -> Self {
+        Session {
+            id,
+            db: 0,
+            name: None,
+            protocol: 2,
+        }
+    }
+}
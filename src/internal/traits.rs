@@ -1,8 +1,11 @@
 use crate::internal::resp::RespValue;
 
 pub trait RespVisitor {
-    fn visit_array(&mut self, array: &Vec<RespValue>) -> RespValue;
+    fn visit_array(&mut self, array: &[RespValue]) -> RespValue;
     fn visit_bulk_string(&mut self, _bulk: &Vec<u8>) -> RespValue {
         RespValue::Null
     }
+    fn visit_other(&mut self, _value: &RespValue) -> RespValue {
+        RespValue::Error(String::from("ERR Protocol error: expected a command array"))
+    }
 }
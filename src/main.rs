@@ -1,60 +1,131 @@
-use std::io::{Read, Write};
-use std::net::{TcpListener, TcpStream};
-use std::thread;
+use std::sync::Arc;
+
+use bytes::BytesMut;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
 
 mod internal;
+use crate::internal::blocking::Blocked;
+use crate::internal::clock::{Clock, SystemClock};
 use crate::internal::cmd::CommandExecutor;
-use crate::internal::resp::{self, RespValue};
+use crate::internal::config::Config;
+use crate::internal::db::Store;
+use crate::internal::expire;
+use crate::internal::resp::{self, ProtoLimits, RespValue};
 
 fn execute_cmd(command: RespValue, executor: &mut CommandExecutor) -> RespValue {
     command.accept(executor)
 }
 
-fn handle_client(mut stream: TcpStream) {
-    let mut executor = CommandExecutor::new();
+/// Parks the connection until a blocking command is served or times out;
+/// `None` if the client went away in the meantime or must be dropped.
+async fn wait_blocked(
+    stream: &mut TcpStream,
+    buffer: &mut BytesMut,
+    limits: &ProtoLimits,
+    mut blocked: Blocked,
+) -> Option<RespValue> {
     loop {
-        let mut buffer = [0; 512];
-        match stream.read(&mut buffer) {
-            Ok(bytes_read) if bytes_read > 0 => {
-                let raw_data = &buffer[..bytes_read];
-                let mut offset = 0;
-                match resp::parse(raw_data, &mut offset) {
-                    Ok(command) => {
-                        println!("Parsed command: {:?}", command);
-                        let response = execute_cmd(command, &mut executor);
-                        let response_bytes = format!("{}", response).into_bytes();
-                        let _ = stream.write_all(&response_bytes);
-                    }
-                    Err(e) => {
-                        eprintln!("Parsing error: {}", e);
-                        let _ = stream.write_all(format!("-ERR {}\r\n", e).as_bytes());
-                    }
+        tokio::select! {
+            reply = blocked.wait() => return Some(reply),
+            read = stream.read_buf(buffer) => match read {
+                Ok(0) | Err(_) => return None,
+                // Anything pipelined behind the blocking command waits its
+                // turn in the buffer, within the same limit as always.
+                Ok(_) if buffer.len() > limits.max_query_buffer => {
+                    let _ = stream
+                        .write_all(b"-ERR Protocol error: client query buffer limit exceeded\r\n")
+                        .await;
+                    return None;
                 }
-            }
-            Err(e) => {
-                eprintln!("Failed to read from  socket: {}", e);
-                break;
-            }
+                Ok(_) => {}
+            },
+        }
+    }
+}
+
+async fn handle_client(
+    mut stream: TcpStream,
+    store: Arc<Store>,
+    clock: Arc<dyn Clock>,
+    limits: Arc<ProtoLimits>,
+) {
+    let mut executor = CommandExecutor::new(store, clock);
+    let mut buffer = BytesMut::with_capacity(4096);
+    loop {
+        match stream.read_buf(&mut buffer).await {
             Ok(0) => {
                 eprintln!("Client closed connection");
                 break;
             }
-            _ => {
+            Ok(_) => {
+                let mut response_bytes = Vec::new();
+                let mut protocol_error = false;
+                loop {
+                    match resp::decode(&mut buffer, &limits) {
+                        Ok(Some(command)) => {
+                            let mut response = execute_cmd(command, &mut executor);
+                            if let Some(blocked) = executor.take_blocked() {
+                                // Earlier pipelined replies go out before parking.
+                                if stream.write_all(&response_bytes).await.is_err() {
+                                    return;
+                                }
+                                response_bytes.clear();
+                                match wait_blocked(&mut stream, &mut buffer, &limits, blocked).await
+                                {
+                                    Some(reply) => response = reply,
+                                    None => return,
+                                }
+                            }
+                            response.encode(executor.protocol(), &mut response_bytes);
+                        }
+                        Ok(None) => break,
+                        Err(e) => {
+                            eprintln!("Parsing error: {}", e);
+                            response_bytes.extend_from_slice(
+                                format!("-ERR Protocol error: {}\r\n", e).as_bytes(),
+                            );
+                            protocol_error = true;
+                            break;
+                        }
+                    }
+                }
+                if stream.write_all(&response_bytes).await.is_err() || protocol_error {
+                    break;
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to read from  socket: {}", e);
                 break;
             }
         }
     }
 }
 
-fn main() -> std::io::Result<()> {
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
     println!("Logs from your program will appear here!");
-    let listener = TcpListener::bind("127.0.0.1:6379").unwrap();
-    for stream in listener.incoming() {
-        match stream {
-            Ok(stream) => {
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let listener = TcpListener::bind(("127.0.0.1", config.port)).await?;
+    let store = Arc::new(Store::new(config.databases));
+    let limits = Arc::new(config.limits);
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    tokio::spawn(expire::run(Arc::clone(&store), Arc::clone(&clock)));
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
                 println!("accepted new connection");
-                thread::spawn(|| {
-                    handle_client(stream);
+                let store = Arc::clone(&store);
+                let clock = Arc::clone(&clock);
+                let limits = Arc::clone(&limits);
+                tokio::spawn(async move {
+                    handle_client(stream, store, clock, limits).await;
                 });
             }
             Err(e) => {
@@ -62,5 +133,4 @@ fn main() -> std::io::Result<()> {
             }
         }
     }
-    Ok(())
 }